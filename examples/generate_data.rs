@@ -0,0 +1,53 @@
+//! Logs a few runs with params, metrics and tags to the Default experiment, to have some data to
+//! look at. The schema is created first if the database is empty.
+//!
+//! MLFLOW_TRACKING_URI=sqlite://mlflowdb.sqlite DEFAULT_ARTIFACT_ROOT=./mlruns \
+//!     cargo run --example generate_data
+use mlflow_rust_server::config::ServerConfig;
+use mlflow_rust_server::entities::{Metric, Param, RunStatus, RunTag};
+use mlflow_rust_server::stores::tracking::error::MlflowError;
+use mlflow_rust_server::stores::tracking::get_store_from_server_config;
+use mlflow_rust_server::utils::{current_time_millis, random_string};
+
+#[tokio::main]
+async fn main() -> Result<(), MlflowError> {
+    let store = get_store_from_server_config(&ServerConfig::from_env()).await?;
+    for i in 0..10 {
+        let run = store.create_run("0", None, None, None, None).await?;
+        let run_id = run.info.run_id.as_str();
+        let x = i as f64 / 10.0;
+        let params = [
+            Param {
+                key: "p1".to_string(),
+                value: x.to_string(),
+            },
+            Param {
+                key: "p2".to_string(),
+                value: (1.0 - x).to_string(),
+            },
+        ];
+        let metric = Metric {
+            key: "m".to_string(),
+            value: x * x,
+            timestamp: current_time_millis(),
+            step: 0,
+        };
+        let tag = RunTag {
+            key: "t".to_string(),
+            value: random_string(),
+        };
+        store
+            .log_batch(run_id, vec![&metric], params.iter().collect(), vec![&tag])
+            .await?;
+        store
+            .update_run(
+                run_id,
+                Some(RunStatus::Finished),
+                Some(current_time_millis()),
+                None,
+            )
+            .await?;
+        println!("Logged run {}", run_id);
+    }
+    Ok(())
+}
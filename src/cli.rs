@@ -14,4 +14,16 @@ pub struct Args {
 
     #[clap(short, long, value_parser, default_value = "5000")]
     pub port: u16,
+
+    /// Maximum number of connections in the database connection pool
+    #[clap(long, value_parser, default_value = "10")]
+    pub pool_size: u32,
+
+    /// Seconds to wait for a database connection before failing a request
+    #[clap(long, value_parser, default_value = "30")]
+    pub pool_acquire_timeout: u64,
+
+    /// Seconds after which an idle database connection is closed (0 keeps it open)
+    #[clap(long, value_parser, default_value = "600")]
+    pub pool_idle_timeout: u64,
 }
@@ -1,10 +1,32 @@
 use crate::env::{DEFAULT_ARTIFACT_ROOT, MLFLOW_TRACKING_URI};
 use std::env;
+use std::time::Duration;
+
+/// Settings of the database connection pool shared by all requests.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    /// How long to wait for a connection before failing the request.
+    pub acquire_timeout: Duration,
+    /// How long a connection can stay idle before it's closed. `None` keeps it open.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(600)),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ServerConfig {
     pub backend_store_uri: String,
     pub default_artifact_root: String,
+    pub pool: PoolConfig,
 }
 
 impl ServerConfig {
@@ -14,6 +36,7 @@ impl ServerConfig {
                 .unwrap_or_else(|_| panic!("{} must be set", MLFLOW_TRACKING_URI)),
             default_artifact_root: env::var(DEFAULT_ARTIFACT_ROOT)
                 .unwrap_or_else(|_| panic!("{} must be set", DEFAULT_ARTIFACT_ROOT)),
+            pool: PoolConfig::default(),
         }
     }
 }
@@ -31,32 +31,149 @@ pub struct ExperimentTag {
     pub value: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Param {
     pub key: String,
     pub value: String,
 }
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct Metric {
     pub key: String,
     pub value: f64,
     pub timestamp: i64,
+    #[serde(default)]
     pub step: i64,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RunTag {
     pub key: String,
     pub value: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(FromRow)]
+pub struct SqlParam {
+    pub key: String,
+    pub value: String,
+    pub run_uuid: String,
+}
+
+impl From<SqlParam> for Param {
+    fn from(p: SqlParam) -> Self {
+        Param {
+            key: p.key,
+            value: p.value,
+        }
+    }
+}
+
+#[derive(FromRow)]
+pub struct SqlMetric {
+    pub key: String,
+    pub value: f64,
+    pub timestamp: i64,
+    pub step: i64,
+    pub is_nan: bool,
+    pub run_uuid: String,
+}
+
+impl From<SqlMetric> for Metric {
+    fn from(m: SqlMetric) -> Self {
+        Metric {
+            key: m.key,
+            value: if m.is_nan { f64::NAN } else { m.value },
+            timestamp: m.timestamp,
+            step: m.step,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MetricWithRunId {
+    pub key: String,
+    pub value: f64,
+    pub timestamp: i64,
+    pub step: i64,
+    pub run_id: String,
+}
+
+impl From<SqlMetric> for MetricWithRunId {
+    fn from(m: SqlMetric) -> Self {
+        MetricWithRunId {
+            key: m.key,
+            value: if m.is_nan { f64::NAN } else { m.value },
+            timestamp: m.timestamp,
+            step: m.step,
+            run_id: m.run_uuid,
+        }
+    }
+}
+
+#[derive(FromRow)]
+pub struct SqlRunTag {
+    pub key: String,
+    pub value: String,
+    pub run_uuid: String,
+}
+
+impl From<SqlRunTag> for RunTag {
+    fn from(t: SqlRunTag) -> Self {
+        RunTag {
+            key: t.key,
+            value: t.value,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub struct RunData {
     pub params: Vec<Param>,
     pub metrics: Vec<Metric>,
     pub tags: Vec<RunTag>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RunStatus {
+    Running,
+    Scheduled,
+    Finished,
+    Failed,
+    Killed,
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RunStatus::Running => write!(f, "RUNNING"),
+            RunStatus::Scheduled => write!(f, "SCHEDULED"),
+            RunStatus::Finished => write!(f, "FINISHED"),
+            RunStatus::Failed => write!(f, "FAILED"),
+            RunStatus::Killed => write!(f, "KILLED"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ViewType {
+    #[default]
+    ActiveOnly,
+    DeletedOnly,
+    All,
+}
+
+impl ViewType {
+    /// The lifecycle stages of the experiments or runs included in this view.
+    pub fn lifecycle_stages(&self) -> Vec<&'static str> {
+        match self {
+            ViewType::ActiveOnly => vec!["active"],
+            ViewType::DeletedOnly => vec!["deleted"],
+            ViewType::All => vec!["active", "deleted"],
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct RunInfo {
     pub name: String,
@@ -66,7 +183,7 @@ pub struct RunInfo {
     pub user_id: String,
     pub status: String,
     pub start_time: i64,
-    pub end_time: i64,
+    pub end_time: Option<i64>,
     pub lifecycle_stage: String,
     pub artifact_uri: String,
 }
@@ -81,13 +198,30 @@ pub struct SqlRun {
     pub user_id: String,
     pub status: String,
     pub start_time: i64,
-    pub end_time: i64,
+    pub end_time: Option<i64>,
     pub source_version: String,
     pub lifecycle_stage: String,
     pub artifact_uri: String,
     pub experiment_id: i32,
 }
 
+impl From<SqlRun> for RunInfo {
+    fn from(r: SqlRun) -> Self {
+        RunInfo {
+            name: r.name,
+            run_uuid: r.run_uuid.clone(),
+            run_id: r.run_uuid,
+            experiment_id: r.experiment_id.to_string(),
+            user_id: r.user_id,
+            status: r.status,
+            start_time: r.start_time,
+            end_time: r.end_time,
+            lifecycle_stage: r.lifecycle_stage,
+            artifact_uri: r.artifact_uri,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Run {
     pub info: RunInfo,
@@ -1,6 +1,6 @@
-use crate::config::ServerConfig;
-use crate::entities::Experiment;
-use crate::stores::tracking::get_store_from_server_config;
+use crate::entities::{Experiment, ExperimentTag, ViewType};
+use crate::handlers::runs::EmptyResponse;
+use crate::stores::tracking::Store;
 use actix_web::{web, HttpResponse, Responder, Result, Scope};
 use serde::{Deserialize, Serialize};
 
@@ -10,63 +10,91 @@ pub struct ListExperimentsResponse {
     pub next_page_token: Option<String>,
 }
 
-pub async fn list_experiments(server_config: web::Data<ServerConfig>) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
-    let experiments = store.list_experiments().await?;
-    store.teardown().await;
+#[derive(Deserialize)]
+pub struct ListExperimentsRequest {
+    max_results: Option<i64>,
+    page_token: Option<String>,
+}
+
+pub async fn list_experiments(
+    store: web::Data<dyn Store>,
+    params: web::Query<ListExperimentsRequest>,
+) -> Result<impl Responder> {
+    let experiments = store
+        .list_experiments(params.max_results, params.page_token.as_deref())
+        .await?;
     Ok(web::Json(ListExperimentsResponse {
-        experiments,
-        next_page_token: None,
+        experiments: experiments.items,
+        next_page_token: experiments.next_page_token,
     }))
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct SearchExperimentsRequest {
     max_results: Option<i64>,
     filter_string: Option<String>,
+    #[serde(default)]
+    view_type: ViewType,
+    #[serde(default)]
+    order_by: Vec<String>,
+    page_token: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 struct SearchExperimentsResponse {
     experiments: Vec<Experiment>,
     next_page_token: Option<String>,
 }
 
 pub async fn search_experiments(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     params: web::Json<SearchExperimentsRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let store = get_store_from_server_config(&server_config).await?;
     let experiments = store
-        .search_experiments(params.max_results, params.filter_string.as_deref(), None)
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
-    store.teardown().await;
+        .search_experiments(
+            params.max_results,
+            params.filter_string.as_deref(),
+            params.view_type,
+            Some(params.order_by.iter().map(|s| &**s).collect()),
+            params.page_token.as_deref(),
+        )
+        .await?;
     Ok(HttpResponse::Ok().json(SearchExperimentsResponse {
-        experiments,
-        next_page_token: None,
+        experiments: experiments.items,
+        next_page_token: experiments.next_page_token,
     }))
 }
 
 #[derive(Deserialize)]
 struct GetExperimentRequest {
-    experiment_id: i32,
+    experiment_id: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 struct GetExperimentResponse {
     experiment: Experiment,
 }
 
 async fn get_experiment(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     params: web::Query<GetExperimentRequest>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
+    let experiment = store.get_experiment(params.experiment_id.as_str()).await?;
+    Ok(web::Json(GetExperimentResponse { experiment }))
+}
+
+#[derive(Deserialize)]
+struct GetExperimentByNameRequest {
+    experiment_name: String,
+}
+
+async fn get_experiment_by_name(
+    store: web::Data<dyn Store>,
+    params: web::Query<GetExperimentByNameRequest>,
+) -> Result<impl Responder> {
     let experiment = store
-        .get_experiment(params.experiment_id.to_string().as_str())
+        .get_experiment_by_name(params.experiment_name.as_str())
         .await?;
-    store.teardown().await;
     Ok(web::Json(GetExperimentResponse { experiment }))
 }
 
@@ -74,6 +102,8 @@ async fn get_experiment(
 pub struct CreateExperimentRequest {
     pub name: String,
     pub artifact_location: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<ExperimentTag>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -82,14 +112,16 @@ pub struct CreateExperimentResponse {
 }
 
 async fn create_experiment(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     data: web::Json<CreateExperimentRequest>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
     let experiment = store
-        .create_experiment(data.name.as_str(), data.artifact_location.as_deref(), None)
+        .create_experiment(
+            data.name.as_str(),
+            data.artifact_location.as_deref(),
+            Some(data.tags.iter().collect()),
+        )
         .await?;
-    store.teardown().await;
     Ok(web::Json(GetExperimentResponse { experiment }))
 }
 
@@ -104,12 +136,10 @@ pub struct DeleteExperimentResponse {
 }
 
 async fn delete_experiment(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     data: web::Json<DeleteExperimentRequest>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
     let experiment = store.delete_experiment(data.experiment_id.as_str()).await?;
-    store.teardown().await;
     Ok(web::Json(DeleteExperimentResponse { experiment }))
 }
 
@@ -124,14 +154,12 @@ pub struct RestoreExperimentResponse {
 }
 
 async fn restore_experiment(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     data: web::Json<RestoreExperimentRequest>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
     let experiment = store
         .restore_experiment(data.experiment_id.as_str())
         .await?;
-    store.teardown().await;
     Ok(web::Json(RestoreExperimentResponse { experiment }))
 }
 
@@ -147,51 +175,91 @@ pub struct UpdateExperimentResponse {
 }
 
 async fn update_experiment(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     data: web::Json<UpdateExperimentRequest>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
     let experiment = store
         .update_experiment(data.experiment_id.as_str(), data.new_name.as_str())
         .await?;
-    store.teardown().await;
     Ok(web::Json(UpdateExperimentResponse { experiment }))
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SetExperimentTagRequest {
+    pub experiment_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+async fn set_experiment_tag(
+    store: web::Data<dyn Store>,
+    data: web::Json<SetExperimentTagRequest>,
+) -> Result<impl Responder> {
+    let data = data.into_inner();
+    store
+        .set_experiment_tag(
+            data.experiment_id.as_str(),
+            &ExperimentTag {
+                key: data.key,
+                value: data.value,
+            },
+        )
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeleteExperimentTagRequest {
+    pub experiment_id: String,
+    pub key: String,
+}
+
+async fn delete_experiment_tag(
+    store: web::Data<dyn Store>,
+    data: web::Json<DeleteExperimentTagRequest>,
+) -> Result<impl Responder> {
+    store
+        .delete_experiment_tag(data.experiment_id.as_str(), data.key.as_str())
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
 pub fn get_scope() -> Scope {
     web::scope("experiments")
         .route("list", web::get().to(list_experiments))
         .route("search", web::post().to(search_experiments))
         .route("get", web::get().to(get_experiment))
+        .route("get-by-name", web::get().to(get_experiment_by_name))
         .route("create", web::post().to(create_experiment))
         .route("delete", web::post().to(delete_experiment))
         .route("restore", web::post().to(restore_experiment))
         .route("update", web::post().to(update_experiment))
+        .route("set-experiment-tag", web::post().to(set_experiment_tag))
+        .route(
+            "delete-experiment-tag",
+            web::post().to(delete_experiment_tag),
+        )
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
         CreateExperimentRequest, CreateExperimentResponse, DeleteExperimentRequest,
-        DeleteExperimentResponse, ListExperimentsResponse, RestoreExperimentRequest,
-        RestoreExperimentResponse, UpdateExperimentRequest, UpdateExperimentResponse,
+        DeleteExperimentResponse, DeleteExperimentTagRequest, GetExperimentResponse,
+        ListExperimentsResponse, RestoreExperimentRequest, RestoreExperimentResponse,
+        SearchExperimentsRequest, SearchExperimentsResponse, SetExperimentTagRequest,
+        UpdateExperimentRequest, UpdateExperimentResponse,
     };
-    use crate::config::ServerConfig;
-    use crate::handlers::{get_api_endpoint, get_service};
+    use crate::entities::{ExperimentTag, ViewType};
+    use crate::handlers::{get_api_endpoint, init_test_service};
+    use crate::stores::tracking::error::ErrorResponse;
     use crate::utils::random_string;
+    use actix_web::http::StatusCode;
     use actix_web::test;
-    use actix_web::{web, App};
 
     #[tokio::test]
     async fn test_list_experiments() {
-        dotenv::from_filename(".env_dev").ok();
-        let server_config = ServerConfig::from_env();
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(server_config.clone()))
-                .service(get_service()),
-        )
-        .await;
+        let app = init_test_service().await;
         let req = test::TestRequest::with_uri(get_api_endpoint("/experiments/list").as_str())
             .to_request();
         let resp = test::call_service(&app, req).await;
@@ -199,25 +267,178 @@ mod tests {
         let body = test::read_body(resp).await;
         let result = String::from_utf8(body.to_vec()).unwrap();
         let list_experiments_resp: ListExperimentsResponse = serde_json::from_str(&result).unwrap();
-        assert!(list_experiments_resp.experiments.len() > 0);
+        assert!(!list_experiments_resp.experiments.is_empty());
     }
 
     #[tokio::test]
-    async fn test_create_experiment() {
-        dotenv::from_filename(".env_dev").ok();
-        let server_config = ServerConfig::from_env();
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(server_config.clone()))
-                .service(get_service()),
+    async fn test_search_experiments_order_by() {
+        let app = init_test_service().await;
+        let prefix = random_string();
+        let names: Vec<String> = ["a", "c", "b"]
+            .iter()
+            .map(|s| format!("{}_{}", prefix, s))
+            .collect();
+        for name in names.iter() {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/experiments/create").as_str())
+                .set_json(&CreateExperimentRequest {
+                    name: name.clone(),
+                    artifact_location: None,
+                    tags: vec![],
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+        }
+
+        for (order_by, expected) in [
+            (vec!["name DESC"], vec!["c", "b", "a"]),
+            (vec!["attributes.name ASC"], vec!["a", "b", "c"]),
+            (vec!["experiment_id DESC"], vec!["b", "c", "a"]),
+            (vec![], vec!["a", "c", "b"]),
+        ] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/experiments/search").as_str())
+                .set_json(&SearchExperimentsRequest {
+                    max_results: None,
+                    filter_string: Some(format!("name LIKE '{}%'", prefix)),
+                    view_type: ViewType::ActiveOnly,
+                    order_by: order_by.iter().map(|s| s.to_string()).collect(),
+                    page_token: None,
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let search_experiments_resp: SearchExperimentsResponse =
+                serde_json::from_str(&result).unwrap();
+            let expected: Vec<String> = expected
+                .iter()
+                .map(|s| format!("{}_{}", prefix, s))
+                .collect();
+            assert_eq!(
+                search_experiments_resp
+                    .experiments
+                    .into_iter()
+                    .map(|e| e.name)
+                    .collect::<Vec<_>>(),
+                expected,
+                "order_by: {:?}",
+                order_by
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_experiments_invalid_parameters() {
+        let app = init_test_service().await;
+        for (body, message) in [
+            (
+                serde_json::json!({"filter_string": "name ~ 'a'"}),
+                "Invalid filter: expected comparator after identifier `name` at column 6, \
+                 found `~`",
+            ),
+            (
+                serde_json::json!({"order_by": ["name DOWN"]}),
+                "Invalid order by clause: expected ASC, DESC or end of input after identifier \
+                 `name` at column 6, found `DOWN`",
+            ),
+            (
+                serde_json::json!({"order_by": ["metrics.loss"]}),
+                "Experiment search only supports ordering by attribute",
+            ),
+            (
+                serde_json::json!({"order_by": ["creation_time DESC"]}),
+                "Invalid attribute key 'creation_time' specified. Valid keys are \
+                 experiment_id, name, artifact_location and lifecycle_stage",
+            ),
+        ] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/experiments/search").as_str())
+                .set_json(&body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+            assert_eq!(error_resp.error_code, "INVALID_PARAMETER_VALUE");
+            assert_eq!(error_resp.message, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_experiment() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::with_uri(
+            format!(
+                "{}?experiment_id={}",
+                get_api_endpoint("/experiments/get"),
+                i32::MAX
+            )
+            .as_str(),
         )
-        .await;
+        .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "RESOURCE_DOES_NOT_EXIST");
+        assert_eq!(
+            error_resp.message,
+            format!("No Experiment with id={} exists", i32::MAX)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_experiment_id() {
+        let app = init_test_service().await;
+        let mut requests = vec![test::TestRequest::with_uri(
+            format!("{}?experiment_id=abc", get_api_endpoint("/experiments/get")).as_str(),
+        )];
+        for (endpoint, body) in [
+            (
+                "/experiments/delete",
+                serde_json::json!({"experiment_id": "abc"}),
+            ),
+            (
+                "/experiments/restore",
+                serde_json::json!({"experiment_id": "abc"}),
+            ),
+            (
+                "/experiments/update",
+                serde_json::json!({"experiment_id": "abc", "new_name": "name"}),
+            ),
+        ] {
+            requests.push(
+                test::TestRequest::post()
+                    .uri(get_api_endpoint(endpoint).as_str())
+                    .set_json(body),
+            );
+        }
+        for req in requests {
+            let resp = test::call_service(&app, req.to_request()).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+            assert_eq!(error_resp.error_code, "INVALID_PARAMETER_VALUE");
+            assert_eq!(error_resp.message, "Invalid experiment id: abc");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_experiment() {
+        let app = init_test_service().await;
         let name = random_string();
         let req = test::TestRequest::post()
             .uri(get_api_endpoint("/experiments/create").as_str())
             .set_json(&CreateExperimentRequest {
                 name: name.clone(),
                 artifact_location: None,
+                tags: vec![],
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
@@ -231,20 +452,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_delete_experiment() {
-        dotenv::from_filename(".env_dev").ok();
-        let server_config = ServerConfig::from_env();
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(server_config.clone()))
-                .service(get_service()),
-        )
-        .await;
+        let app = init_test_service().await;
         let name = random_string();
         let req = test::TestRequest::post()
             .uri(get_api_endpoint("/experiments/create").as_str())
             .set_json(&CreateExperimentRequest {
                 name: name.clone(),
                 artifact_location: None,
+                tags: vec![],
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
@@ -271,14 +486,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_restore_experiment() {
-        dotenv::from_filename(".env_dev").ok();
-        let server_config = ServerConfig::from_env();
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(server_config.clone()))
-                .service(get_service()),
-        )
-        .await;
+        let app = init_test_service().await;
 
         let name = random_string();
         let req = test::TestRequest::post()
@@ -286,6 +494,7 @@ mod tests {
             .set_json(&CreateExperimentRequest {
                 name: name.clone(),
                 artifact_location: None,
+                tags: vec![],
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
@@ -326,14 +535,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_update_experiment() {
-        dotenv::from_filename(".env_dev").ok();
-        let server_config = ServerConfig::from_env();
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(server_config.clone()))
-                .service(get_service()),
-        )
-        .await;
+        let app = init_test_service().await;
 
         let name = random_string();
         let req = test::TestRequest::post()
@@ -341,6 +543,7 @@ mod tests {
             .set_json(&CreateExperimentRequest {
                 name: name.clone(),
                 artifact_location: None,
+                tags: vec![],
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
@@ -366,4 +569,210 @@ mod tests {
             serde_json::from_str(&result).unwrap();
         assert_eq!(update_experiment_resp.experiment.name, new_name);
     }
+
+    #[tokio::test]
+    async fn test_experiment_tags() {
+        let app = init_test_service().await;
+        let tag = |key: &str, value: &str| ExperimentTag {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/experiments/create").as_str())
+            .set_json(&CreateExperimentRequest {
+                name: random_string(),
+                artifact_location: None,
+                tags: vec![tag("a", "1")],
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_experiment_resp: CreateExperimentResponse =
+            serde_json::from_str(&result).unwrap();
+        let experiment_id = create_experiment_resp.experiment.experiment_id;
+        assert_eq!(create_experiment_resp.experiment.tags, vec![tag("a", "1")]);
+
+        for (key, value) in [("b", "2"), ("a", "3")] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/experiments/set-experiment-tag").as_str())
+                .set_json(&SetExperimentTagRequest {
+                    experiment_id: experiment_id.clone(),
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+        }
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/experiments/delete-experiment-tag").as_str())
+            .set_json(&DeleteExperimentTagRequest {
+                experiment_id: experiment_id.clone(),
+                key: "b".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::with_uri(
+            format!(
+                "{}?experiment_id={}",
+                get_api_endpoint("/experiments/get"),
+                experiment_id
+            )
+            .as_str(),
+        )
+        .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let get_experiment_resp: GetExperimentResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(get_experiment_resp.experiment.tags, vec![tag("a", "3")]);
+
+        // Deleting a missing tag is not found
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/experiments/delete-experiment-tag").as_str())
+            .set_json(&DeleteExperimentTagRequest {
+                experiment_id: experiment_id.clone(),
+                key: "b".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "RESOURCE_DOES_NOT_EXIST");
+        assert_eq!(
+            error_resp.message,
+            format!(
+                "No tag with name: b in experiment with id {}",
+                experiment_id
+            )
+        );
+
+        // Tagging a missing experiment is not found
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/experiments/set-experiment-tag").as_str())
+            .set_json(&SetExperimentTagRequest {
+                experiment_id: i32::MAX.to_string(),
+                key: "a".to_string(),
+                value: "1".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_get_experiment_by_name() {
+        let app = init_test_service().await;
+        let name = random_string();
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/experiments/create").as_str())
+            .set_json(&CreateExperimentRequest {
+                name: name.clone(),
+                artifact_location: None,
+                tags: vec![],
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_experiment_resp: CreateExperimentResponse =
+            serde_json::from_str(&result).unwrap();
+
+        let req = test::TestRequest::with_uri(
+            format!(
+                "{}?experiment_name={}",
+                get_api_endpoint("/experiments/get-by-name"),
+                name
+            )
+            .as_str(),
+        )
+        .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let get_experiment_resp: GetExperimentResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            get_experiment_resp.experiment.experiment_id,
+            create_experiment_resp.experiment.experiment_id
+        );
+
+        let missing = random_string();
+        let req = test::TestRequest::with_uri(
+            format!(
+                "{}?experiment_name={}",
+                get_api_endpoint("/experiments/get-by-name"),
+                missing
+            )
+            .as_str(),
+        )
+        .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "RESOURCE_DOES_NOT_EXIST");
+        assert_eq!(
+            error_resp.message,
+            format!("Could not find experiment with name '{}'", missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_experiment_name() {
+        let app = init_test_service().await;
+        let name = random_string();
+        let other_name = random_string();
+        let mut experiment_id = String::new();
+        for name in [&name, &other_name] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/experiments/create").as_str())
+                .set_json(&CreateExperimentRequest {
+                    name: name.clone(),
+                    artifact_location: None,
+                    tags: vec![],
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let create_experiment_resp: CreateExperimentResponse =
+                serde_json::from_str(&result).unwrap();
+            experiment_id = create_experiment_resp.experiment.experiment_id;
+        }
+
+        for (endpoint, body) in [
+            ("/experiments/create", serde_json::json!({"name": name})),
+            (
+                "/experiments/update",
+                serde_json::json!({"experiment_id": experiment_id, "new_name": name}),
+            ),
+        ] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint(endpoint).as_str())
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", endpoint);
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+            assert_eq!(error_resp.error_code, "RESOURCE_ALREADY_EXISTS");
+            assert_eq!(
+                error_resp.message,
+                format!("Experiment(name={}) already exists", name)
+            );
+        }
+    }
 }
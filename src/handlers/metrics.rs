@@ -0,0 +1,261 @@
+use crate::entities::{Metric, MetricWithRunId};
+use crate::stores::tracking::error::MlflowError;
+use crate::stores::tracking::Store;
+use actix_web::{web, HttpRequest, Responder, Result, Scope};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMetricHistoryParams {
+    pub run_id: String,
+    pub metric_key: String,
+    pub max_results: Option<i64>,
+    pub page_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMetricHistoryResponse {
+    pub metrics: Vec<Metric>,
+    pub next_page_token: Option<String>,
+}
+
+async fn get_metric_history(
+    store: web::Data<dyn Store>,
+    params: web::Query<GetMetricHistoryParams>,
+) -> Result<impl Responder> {
+    let metrics = store
+        .get_metric_history(
+            params.run_id.as_str(),
+            params.metric_key.as_str(),
+            params.max_results,
+            params.page_token.as_deref(),
+        )
+        .await?;
+    Ok(web::Json(GetMetricHistoryResponse {
+        metrics: metrics.items,
+        next_page_token: metrics.next_page_token,
+    }))
+}
+
+#[derive(Default)]
+pub struct GetMetricHistoryBulkIntervalParams {
+    pub run_ids: Vec<String>,
+    pub metric_key: String,
+    pub start_step: Option<i64>,
+    pub end_step: Option<i64>,
+    pub max_results: Option<i64>,
+}
+
+impl GetMetricHistoryBulkIntervalParams {
+    /// Parses the query string by hand because `run_ids` is repeated once per run
+    /// (`run_ids=a&run_ids=b`), which `web::Query` can't deserialize into a `Vec`.
+    fn from_query(query: &str) -> Result<Self, MlflowError> {
+        let parse_i64 = |key: &str, value: &str| {
+            value.parse::<i64>().map_err(|_| {
+                MlflowError::InvalidParameter(format!("Invalid value for {}: {}", key, value))
+            })
+        };
+        let mut params = GetMetricHistoryBulkIntervalParams::default();
+        let mut metric_key = None;
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "run_ids" => params.run_ids.push(value.into_owned()),
+                "metric_key" => metric_key = Some(value.into_owned()),
+                "start_step" => params.start_step = Some(parse_i64(&key, &value)?),
+                "end_step" => params.end_step = Some(parse_i64(&key, &value)?),
+                "max_results" => params.max_results = Some(parse_i64(&key, &value)?),
+                _ => {}
+            }
+        }
+        params.metric_key = metric_key.ok_or_else(|| {
+            MlflowError::InvalidParameter(
+                "Missing value for required parameter 'metric_key'".to_string(),
+            )
+        })?;
+        Ok(params)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMetricHistoryBulkIntervalResponse {
+    pub metrics: Vec<MetricWithRunId>,
+}
+
+async fn get_metric_history_bulk_interval(
+    store: web::Data<dyn Store>,
+    req: HttpRequest,
+) -> Result<impl Responder> {
+    let params = GetMetricHistoryBulkIntervalParams::from_query(req.query_string())?;
+    let metrics = store
+        .get_metric_history_bulk_interval(
+            params.run_ids.iter().map(|s| &**s).collect(),
+            params.metric_key.as_str(),
+            params.start_step,
+            params.end_step,
+            params.max_results,
+        )
+        .await?;
+    Ok(web::Json(GetMetricHistoryBulkIntervalResponse { metrics }))
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("metrics")
+        .route("get-history", web::get().to(get_metric_history))
+        .route(
+            "get-history-bulk-interval",
+            web::get().to(get_metric_history_bulk_interval),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GetMetricHistoryBulkIntervalResponse, GetMetricHistoryResponse};
+    use crate::handlers::runs::{CreateRunRequest, CreateRunResponse, LogMetricRequest};
+    use crate::handlers::{get_api_endpoint, init_test_service};
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+
+    #[tokio::test]
+    async fn test_get_metric_history() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .set_json(&CreateRunRequest {
+                experiment_id: "0".to_string(),
+                user_id: None,
+                start_time: None,
+                run_name: None,
+                tags: None,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+        let run_id = create_run_resp.run.info.run_id;
+
+        for step in (0..3).rev() {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/runs/log-metric").as_str())
+                .set_json(&LogMetricRequest {
+                    run_id: run_id.clone(),
+                    key: "m".to_string(),
+                    value: step as f64,
+                    timestamp: 0,
+                    step: Some(step),
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+        }
+
+        let req = test::TestRequest::get()
+            .uri(
+                format!(
+                    "{}?run_id={}&metric_key=m&max_results=2",
+                    get_api_endpoint("/metrics/get-history"),
+                    run_id
+                )
+                .as_str(),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let get_metric_history_resp: GetMetricHistoryResponse =
+            serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            get_metric_history_resp
+                .metrics
+                .iter()
+                .map(|m| m.step)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert!(get_metric_history_resp.next_page_token.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_metric_history_bulk_interval() {
+        let app = init_test_service().await;
+        let mut run_ids = vec![];
+        for _ in 0..2 {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/runs/create").as_str())
+                .set_json(&CreateRunRequest {
+                    experiment_id: "0".to_string(),
+                    user_id: None,
+                    start_time: None,
+                    run_name: None,
+                    tags: None,
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+            let run_id = create_run_resp.run.info.run_id;
+            for step in 0..10 {
+                let req = test::TestRequest::post()
+                    .uri(get_api_endpoint("/runs/log-metric").as_str())
+                    .set_json(&LogMetricRequest {
+                        run_id: run_id.clone(),
+                        key: "m".to_string(),
+                        value: step as f64,
+                        timestamp: 0,
+                        step: Some(step),
+                    })
+                    .to_request();
+                let resp = test::call_service(&app, req).await;
+                assert!(resp.status().is_success());
+            }
+            run_ids.push(run_id);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(
+                format!(
+                    "{}?run_ids={}&run_ids={}&metric_key=m&start_step=1&max_results=3",
+                    get_api_endpoint("/metrics/get-history-bulk-interval"),
+                    run_ids[1],
+                    run_ids[0]
+                )
+                .as_str(),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let bulk_resp: GetMetricHistoryBulkIntervalResponse =
+            serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            bulk_resp
+                .metrics
+                .iter()
+                .map(|m| (m.run_id.as_str(), m.step))
+                .collect::<Vec<_>>(),
+            vec![
+                (run_ids[1].as_str(), 1),
+                (run_ids[1].as_str(), 5),
+                (run_ids[1].as_str(), 9),
+                (run_ids[0].as_str(), 1),
+                (run_ids[0].as_str(), 5),
+                (run_ids[0].as_str(), 9),
+            ]
+        );
+
+        let req = test::TestRequest::get()
+            .uri(
+                format!(
+                    "{}?run_ids={}",
+                    get_api_endpoint("/metrics/get-history-bulk-interval"),
+                    run_ids[0]
+                )
+                .as_str(),
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
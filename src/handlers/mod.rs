@@ -1,16 +1,130 @@
 pub mod experiments;
+pub mod metrics;
 pub mod runs;
+use crate::stores::tracking::error::MlflowError;
 use actix_web::{web, Scope};
 
-// const API_PREFIX: &str = "/api/2.0/mlflow";
-const API_PREFIX: &str = "/ajax-api/2.0/preview/mlflow";
+/// The prefixes the API is served under. MLflow clients call the `/api` routes and the MLflow UI
+/// calls the `/ajax-api` ones.
+pub const API_PREFIXES: [&str; 4] = [
+    "/api/2.0/mlflow",
+    "/api/2.0/preview/mlflow",
+    "/ajax-api/2.0/mlflow",
+    "/ajax-api/2.0/preview/mlflow",
+];
 
-pub fn get_service() -> Scope {
-    web::scope(API_PREFIX)
+fn get_api_scope(prefix: &str) -> Scope {
+    web::scope(prefix)
         .service(experiments::get_scope())
         .service(runs::get_scope())
+        .service(metrics::get_scope())
+}
+
+pub fn get_service() -> Scope {
+    API_PREFIXES.iter().fold(web::scope(""), |scope, prefix| {
+        scope.service(get_api_scope(prefix))
+    })
+}
+
+/// Makes malformed JSON bodies answer with the same JSON error response as the handlers.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .error_handler(|err, _req| MlflowError::BadRequest(err.to_string()).into())
+}
+
+/// Makes malformed query strings answer with the same JSON error response as the handlers.
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|err, _req| MlflowError::BadRequest(err.to_string()).into())
 }
 
 pub fn get_api_endpoint(endpoint: &str) -> String {
-    format!("{}{}", API_PREFIX, endpoint)
+    format!("{}{}", API_PREFIXES[0], endpoint)
+}
+
+/// Builds the API service on top of the store configured in `.env_dev`, for handler tests.
+#[cfg(test)]
+pub(crate) async fn init_test_service() -> impl actix_web::dev::Service<
+    actix_http::Request,
+    Response = actix_web::dev::ServiceResponse,
+    Error = actix_web::Error,
+> {
+    use crate::config::ServerConfig;
+    use crate::stores::tracking::get_store_from_server_config;
+    use actix_web::App;
+
+    dotenv::from_filename(".env_dev").ok();
+    let store = get_store_from_server_config(&ServerConfig::from_env())
+        .await
+        .unwrap();
+    actix_web::test::init_service(
+        App::new()
+            .app_data(web::Data::from(store))
+            .app_data(json_config())
+            .app_data(query_config())
+            .service(get_service()),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_api_endpoint, init_test_service, API_PREFIXES};
+    use crate::stores::tracking::error::ErrorResponse;
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+
+    #[tokio::test]
+    async fn test_api_prefixes() {
+        let app = init_test_service().await;
+        for prefix in API_PREFIXES {
+            let req =
+                test::TestRequest::with_uri(&format!("{}/experiments/list", prefix)).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success(), "{}", prefix);
+
+            let req = test::TestRequest::post()
+                .uri(&format!("{}/experiments/search", prefix))
+                .set_json(serde_json::json!({}))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success(), "{}", prefix);
+        }
+
+        for uri in [
+            "/api/2.1/mlflow/experiments/list",
+            "/mlflow/experiments/list",
+            "/api/2.0/mlflow/preview/experiments/list",
+        ] {
+            let req = test::TestRequest::with_uri(uri).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_requests() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .insert_header(("content-type", "application/json"))
+            .set_payload("{\"experiment_id\": ")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "BAD_REQUEST");
+        assert!(error_resp.message.starts_with("Json deserialize error"));
+
+        let req = test::TestRequest::with_uri(get_api_endpoint("/runs/get").as_str()).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "BAD_REQUEST");
+        assert!(error_resp.message.contains("run_id"));
+    }
 }
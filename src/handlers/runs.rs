@@ -1,346 +1,17 @@
-use crate::config::ServerConfig;
-use crate::entities::Run;
-use crate::stores::tracking::get_store_from_server_config;
+use crate::entities::{Metric, Param, Run, RunInfo, RunStatus, RunTag, ViewType};
+use crate::stores::tracking::Store;
 use actix_web::{web, Responder, Result, Scope};
 use serde::{Deserialize, Serialize};
 
-// use regex::Regex;
-// use crate::proto::mlflow::RunStatus;
-// use crate::proto::mlflow::{
-//     search_runs::Response as SearchRunsResponse, Metric, Param, Run, RunData, RunInfo, RunTag,
-//     SearchRuns, ViewType,
-// };
-
-// #[derive(Debug, sqlx::FromRow, Serialize)]
-// struct ExperimentTag {
-//     run_uuid: String,
-//     key: String,
-//     value: String,
-// }
-
-// impl ViewType {
-//     pub fn from_str<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
-//     where
-//         D: serde::Deserializer<'de>,
-//     {
-//         let s: &str = Deserialize::deserialize(deserializer)?;
-//         match s.to_lowercase().as_str() {
-//             "ACTIVE_ONLY" => Ok(Some(Self::ActiveOnly as i32)),
-//             "DELETED_ONLY" => Ok(Some(Self::DeletedOnly as i32)),
-//             "ALL" => Ok(Some(Self::All as i32)),
-//             _ => Err(serde::de::Error::custom(format!("Invalid ViewType: {}", s))),
-//         }
-//     }
-// }
-
-// impl<'r> FromRow<'r, PgRow> for Run {
-//     fn from_row(row: &'r PgRow) -> Result<Self, Error> {
-//         let run_uuid: String = row.try_get("run_uuid")?;
-//         let user_id = row.try_get("user_id")?;
-//         let status = row.try_get("status")?;
-//         let start_time = row.try_get("start_time")?;
-//         let end_time = row.try_get("end_time")?;
-//         let lifecycle_stage = row.try_get("lifecycle_stage")?;
-//         let artifact_uri = row.try_get("artifact_uri")?;
-//         let experiment_id: i32 = row.try_get("experiment_id")?;
-
-//         let info = RunInfo {
-//             run_uuid: Some(run_uuid.clone()),
-//             run_id: Some(run_uuid.clone()),
-//             user_id,
-//             status,
-//             start_time,
-//             end_time,
-//             lifecycle_stage,
-//             artifact_uri,
-//             experiment_id: Some(experiment_id.to_string()),
-//         };
-//         let data = RunData {
-//             params: vec![],
-//             metrics: vec![],
-//             tags: vec![],
-//         };
-//         Ok(Run {
-//             info: Some(info),
-//             data: Some(data),
-//         })
-//     }
-// }
-
-// impl<'r> FromRow<'r, PgRow> for Param {
-//     fn from_row(row: &'r PgRow) -> Result<Self, Error> {
-//         let key: String = row.try_get("key")?;
-//         let value: String = row.try_get("value")?;
-//         Ok(Param {
-//             key: Some(key),
-//             value: Some(value),
-//         })
-//     }
-// }
-
-// impl<'r> FromRow<'r, PgRow> for Metric {
-//     fn from_row(row: &'r PgRow) -> Result<Self, Error> {
-//         let key: String = row.try_get("key")?;
-//         let value: f64 = row.try_get("value")?;
-//         let timestamp: i64 = row.try_get("timestamp")?;
-//         let step: i64 = row.try_get("step")?;
-//         Ok(Metric {
-//             key: Some(key),
-//             value: Some(value),
-//             timestamp: Some(timestamp),
-//             step: Some(step),
-//         })
-//     }
-// }
-
-// impl<'r> FromRow<'r, PgRow> for RunTag {
-//     fn from_row(row: &'r PgRow) -> Result<Self, Error> {
-//         let key: String = row.try_get("key")?;
-//         let value: String = row.try_get("value")?;
-//         Ok(RunTag {
-//             key: Some(key),
-//             value: Some(value),
-//         })
-//     }
-// }
-
-// pub async fn list_runs(pool: web::Data<PgPool>) -> Result<impl Responder> {
-//     let runs: Vec<Run> = sqlx::query_as(r#"SELECT * FROM runs"#)
-//         .fetch_all(pool.get_ref())
-//         .await
-//         .unwrap();
-//     Ok(web::Json(runs))
-// }
-
-// #[derive(Debug, sqlx::FromRow, Serialize)]
-// struct SqlRun {
-//     run_uuid: String,
-//     name: String,
-//     source_type: String,
-//     source_name: String,
-//     entry_point_name: String,
-//     user_id: String,
-//     status: String,
-//     start_time: i64,
-//     end_time: i64,
-//     source_version: String,
-//     lifecycle_stage: String,
-//     artifact_uri: String,
-//     experiment_id: i32,
-// }
-
-// fn view_type_to_stages(view_type: ViewType) -> Vec<&'static str> {
-//     match view_type {
-//         ViewType::ActiveOnly => vec!["active"],
-//         ViewType::DeletedOnly => vec!["deleted"],
-//         ViewType::All => vec!["active", "deleted"],
-//     }
-// }
-
-// struct Comparison<'a> {
-//     key: &'a str,
-//     comparator: &'a str,
-//     value: &'a str,
-// }
-
-// impl<'a> Comparison<'a> {
-//     fn to_query(&self) -> String {
-//         format!(
-//             "key = '{}' AND value {} {}",
-//             self.key, self.comparator, self.value
-//         )
-//     }
-// }
-
-// {
-//     let s: &str = serde::Deserialize::deserialize(deserializer)?;
-//     match s {
-//         "RUNNING" => Ok(Some(RunStatus::Running as i32)),
-//         "SCHEDULE" => Ok(Some(RunStatus::Scheduled as i32)),
-//         "FINISHED" => Ok(Some(RunStatus::Finished as i32)),
-//         "FAILED" => Ok(Some(RunStatus::Failed as i32)),
-//         "KILLED" => Ok(Some(RunStatus::Killed as i32)),
-//         _ => Err(serde::de::Error::custom(format!("Invalid ViewType: {}", s))),
-//     }
-// }
-
-// fn run_status_string_to_int(status: &str) -> Result<i32, Box<dyn std::error::Error>> {
-//     match status {
-//         "RUNNING" => Ok(RunStatus::Running as i32),
-//         "SCHEDULED" => Ok(RunStatus::Scheduled as i32),
-//         "FINISHED" => Ok(RunStatus::Finished as i32),
-//         "FAILED" => Ok(RunStatus::Failed as i32),
-//         "KILLED" => Ok(RunStatus::Killed as i32),
-//         _ => Err(format!("Invalid RunStatus: {}", status).into()),
-//     }
-// }
-
-// pub async fn search_runs(
-//     pool: web::Data<PgPool>,
-//     payload: web::Json<SearchRuns>,
-// ) -> Result<impl Responder> {
-//     let stages =
-//         view_type_to_stages(ViewType::from_i32(payload.run_view_type.unwrap_or(0)).unwrap());
-//     let re = Regex::new(
-//         r"((?P<type>(params|metrics|tags|attributes))\.)?(?P<key>.+) (?P<comparator>(=|!=|<|<=|>|>=|LIKE|ILIKE)) (?P<value>.+)",
-//     )
-//     .unwrap();
-//     let filter_query = match &payload.filter {
-//         Some(filter) if (filter != "") => {
-//             let clauses = filter.split("AND").map(|x| x.trim()).collect::<Vec<_>>();
-//             let query = clauses.into_iter().fold("runs".to_string(), |acc, clause| {
-//                 let caps = re.captures(clause).unwrap();
-//                 let typ = caps.name("type").unwrap().as_str();
-//                 let comparison = Comparison {
-//                     key: caps.name("key").unwrap().as_str(),
-//                     comparator: caps.name("comparator").unwrap().as_str(),
-//                     value: caps.name("value").unwrap().as_str(),
-//                 };
-//                 let query = comparison.to_query();
-//                 format!(
-//                     r#"
-//                         (
-//                             SELECT
-//                                 runs.*
-//                             FROM
-//                                 {}
-//                             JOIN (
-//                                 SELECT
-//                                     DISTINCT ON (run_uuid) run_uuid
-//                                 FROM
-//                                     {}
-//                                 WHERE
-//                                     {}
-//                             ) filtered
-//                             ON
-//                                 filtered.run_uuid = runs.run_uuid
-//                         ) runs
-//                         "#,
-//                     acc, typ, query
-//                 )
-//             });
-//             query
-//         }
-//         None => "runs".to_string(),
-//         _ => "runs".to_string(),
-//     };
-
-//     let sql_runs: Vec<SqlRun> = sqlx::query_as(&format!(
-//         r#"
-// SELECT *
-// FROM {}
-// WHERE lifecycle_stage = ANY($1) LIMIT $2
-// "#,
-//         filter_query
-//     ))
-//     .bind(stages)
-//     .bind(payload.max_results.unwrap_or(3))
-//     .fetch_all(pool.get_ref())
-//     .await
-//     .unwrap();
-
-//     let mut runs: Vec<Run> = vec![];
-//     for sql_run in sql_runs {
-//         let params: Vec<Param> = sqlx::query_as(r#"SELECT * FROM params WHERE run_uuid = $1"#)
-//             .bind(sql_run.run_uuid.as_str())
-//             .fetch_all(pool.get_ref())
-//             .await
-//             .unwrap();
-//         let metrics: Vec<Metric> = sqlx::query_as(r#"SELECT * FROM metrics WHERE run_uuid = $1"#)
-//             .bind(sql_run.run_uuid.as_str())
-//             .fetch_all(pool.get_ref())
-//             .await
-//             .unwrap();
-//         let tags: Vec<RunTag> = sqlx::query_as(r#"SELECT * FROM tags WHERE run_uuid = $1"#)
-//             .bind(sql_run.run_uuid.as_str())
-//             .fetch_all(pool.get_ref())
-//             .await
-//             .unwrap();
-//         let run = Run {
-//             info: Some(RunInfo {
-//                 run_uuid: Some(sql_run.run_uuid.clone()),
-//                 run_id: Some(sql_run.run_uuid),
-//                 experiment_id: Some(sql_run.experiment_id.to_string()),
-//                 user_id: Some(sql_run.user_id),
-//                 status: Some(run_status_string_to_int(&sql_run.status).unwrap()),
-//                 start_time: Some(sql_run.start_time),
-//                 end_time: Some(sql_run.end_time),
-//                 lifecycle_stage: Some(sql_run.lifecycle_stage),
-//                 artifact_uri: Some(sql_run.artifact_uri),
-//             }),
-//             data: Some(RunData {
-//                 params,
-//                 metrics,
-//                 tags,
-//             }),
-//         };
-//         runs.push(run);
-//     }
-
-//     Ok(web::Json(SearchRunsResponse {
-//         runs,
-//         next_page_token: None,
-//     }))
-// }
-
-// #[derive(Deserialize)]
-// pub struct GetRunParams {
-//     run_id: String,
-// }
-
-// #[derive(Serialize)]
-// struct GetRunResponse {
-//     run: Run,
-// }
-
-// pub async fn get_run(
-//     pool: web::Data<PgPool>,
-//     params: web::Query<GetRunParams>,
-// ) -> Result<impl Responder> {
-//     let run: SqlRun = sqlx::query_as(r#"SELECT * FROM runs WHERE run_uuid = $1"#)
-//         .bind(params.run_id.as_str())
-//         .fetch_one(pool.get_ref())
-//         .await
-//         .unwrap();
-//     let params: Vec<Param> = sqlx::query_as(r#"SELECT * FROM params WHERE run_uuid = $1"#)
-//         .bind(run.run_uuid.as_str())
-//         .fetch_all(pool.get_ref())
-//         .await
-//         .unwrap();
-//     let metrics: Vec<Metric> = sqlx::query_as(r#"SELECT * FROM metrics WHERE run_uuid = $1"#)
-//         .bind(run.run_uuid.as_str())
-//         .fetch_all(pool.get_ref())
-//         .await
-//         .unwrap();
-//     let tags: Vec<RunTag> = sqlx::query_as(r#"SELECT * FROM tags WHERE run_uuid = $1"#)
-//         .bind(run.run_uuid.as_str())
-//         .fetch_all(pool.get_ref())
-//         .await
-//         .unwrap();
-//     let run = Run {
-//         info: Some(RunInfo {
-//             run_uuid: Some(run.run_uuid.clone()),
-//             run_id: Some(run.run_uuid),
-//             user_id: Some(run.user_id),
-//             status: Some(run.status.parse().unwrap()),
-//             start_time: Some(run.start_time),
-//             end_time: Some(run.end_time),
-//             artifact_uri: Some(run.artifact_uri),
-//             experiment_id: Some(run.experiment_id.to_string()),
-//             lifecycle_stage: Some(run.lifecycle_stage),
-//         }),
-//         data: Some(RunData {
-//             params,
-//             metrics,
-//             tags,
-//         }),
-//     };
-//     Ok(web::Json(GetRunResponse { run }))
-// }
-
 #[derive(Serialize, Deserialize)]
 pub struct SearchRunsRequest {
     experiment_ids: Vec<String>,
+    filter: Option<String>,
+    #[serde(default)]
+    run_view_type: ViewType,
+    order_by: Option<Vec<String>>,
+    max_results: Option<i64>,
+    page_token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -350,17 +21,24 @@ struct SearchRunsResponse {
 }
 
 pub async fn search_runs(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     data: web::Json<SearchRunsRequest>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
     let runs = store
-        .search_runs(data.experiment_ids.iter().map(|s| &**s).collect())
+        .search_runs(
+            data.experiment_ids.iter().map(|s| &**s).collect(),
+            data.filter.as_deref(),
+            data.run_view_type,
+            data.order_by
+                .as_ref()
+                .map(|v| v.iter().map(|s| &**s).collect()),
+            data.max_results,
+            data.page_token.as_deref(),
+        )
         .await?;
-    store.teardown().await;
     Ok(web::Json(SearchRunsResponse {
-        runs,
-        next_page_token: None,
+        runs: runs.items,
+        next_page_token: runs.next_page_token,
     }))
 }
 
@@ -375,43 +53,268 @@ pub struct GetRunResponse {
 }
 
 async fn get_run(
-    server_config: web::Data<ServerConfig>,
+    store: web::Data<dyn Store>,
     params: web::Query<GetRunParams>,
 ) -> Result<impl Responder> {
-    let store = get_store_from_server_config(&server_config).await?;
     let run = store.get_run(&params.run_id.to_string()).await?;
-    store.teardown().await;
     Ok(web::Json(GetRunResponse { run }))
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CreateRunRequest {
+    pub experiment_id: String,
+    pub user_id: Option<String>,
+    pub start_time: Option<i64>,
+    pub run_name: Option<String>,
+    pub tags: Option<Vec<RunTag>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateRunResponse {
+    pub run: Run,
+}
+
+async fn create_run(
+    store: web::Data<dyn Store>,
+    data: web::Json<CreateRunRequest>,
+) -> Result<impl Responder> {
+    let run = store
+        .create_run(
+            data.experiment_id.as_str(),
+            data.user_id.as_deref(),
+            data.start_time,
+            data.run_name.as_deref(),
+            data.tags.as_ref().map(|tags| tags.iter().collect()),
+        )
+        .await?;
+    Ok(web::Json(CreateRunResponse { run }))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateRunRequest {
+    pub run_id: String,
+    pub status: Option<RunStatus>,
+    pub end_time: Option<i64>,
+    pub run_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateRunResponse {
+    pub run_info: RunInfo,
+}
+
+async fn update_run(
+    store: web::Data<dyn Store>,
+    data: web::Json<UpdateRunRequest>,
+) -> Result<impl Responder> {
+    let run_info = store
+        .update_run(
+            data.run_id.as_str(),
+            data.status,
+            data.end_time,
+            data.run_name.as_deref(),
+        )
+        .await?;
+    Ok(web::Json(UpdateRunResponse { run_info }))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeleteRunRequest {
+    pub run_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeleteRunResponse {
+    pub run: Run,
+}
+
+async fn delete_run(
+    store: web::Data<dyn Store>,
+    data: web::Json<DeleteRunRequest>,
+) -> Result<impl Responder> {
+    let run = store.delete_run(data.run_id.as_str()).await?;
+    Ok(web::Json(DeleteRunResponse { run }))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RestoreRunRequest {
+    pub run_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RestoreRunResponse {
+    pub run: Run,
+}
+
+async fn restore_run(
+    store: web::Data<dyn Store>,
+    data: web::Json<RestoreRunRequest>,
+) -> Result<impl Responder> {
+    let run = store.restore_run(data.run_id.as_str()).await?;
+    Ok(web::Json(RestoreRunResponse { run }))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EmptyResponse {}
+
+#[derive(Serialize, Deserialize)]
+pub struct LogMetricRequest {
+    pub run_id: String,
+    pub key: String,
+    pub value: f64,
+    pub timestamp: i64,
+    pub step: Option<i64>,
+}
+
+async fn log_metric(
+    store: web::Data<dyn Store>,
+    data: web::Json<LogMetricRequest>,
+) -> Result<impl Responder> {
+    let data = data.into_inner();
+    store
+        .log_metric(
+            data.run_id.as_str(),
+            &Metric {
+                key: data.key,
+                value: data.value,
+                timestamp: data.timestamp,
+                step: data.step.unwrap_or(0),
+            },
+        )
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LogParamRequest {
+    pub run_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+async fn log_param(
+    store: web::Data<dyn Store>,
+    data: web::Json<LogParamRequest>,
+) -> Result<impl Responder> {
+    let data = data.into_inner();
+    store
+        .log_param(
+            data.run_id.as_str(),
+            &Param {
+                key: data.key,
+                value: data.value,
+            },
+        )
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetTagRequest {
+    pub run_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+async fn set_tag(
+    store: web::Data<dyn Store>,
+    data: web::Json<SetTagRequest>,
+) -> Result<impl Responder> {
+    let data = data.into_inner();
+    store
+        .set_tag(
+            data.run_id.as_str(),
+            &RunTag {
+                key: data.key,
+                value: data.value,
+            },
+        )
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeleteTagRequest {
+    pub run_id: String,
+    pub key: String,
+}
+
+async fn delete_tag(
+    store: web::Data<dyn Store>,
+    data: web::Json<DeleteTagRequest>,
+) -> Result<impl Responder> {
+    store
+        .delete_tag(data.run_id.as_str(), data.key.as_str())
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LogBatchRequest {
+    pub run_id: String,
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub params: Vec<Param>,
+    #[serde(default)]
+    pub tags: Vec<RunTag>,
+}
+
+async fn log_batch(
+    store: web::Data<dyn Store>,
+    data: web::Json<LogBatchRequest>,
+) -> Result<impl Responder> {
+    store
+        .log_batch(
+            data.run_id.as_str(),
+            data.metrics.iter().collect(),
+            data.params.iter().collect(),
+            data.tags.iter().collect(),
+        )
+        .await?;
+    Ok(web::Json(EmptyResponse {}))
+}
+
 pub fn get_scope() -> Scope {
     web::scope("runs")
         .route("search", web::post().to(search_runs))
         .route("get", web::get().to(get_run))
+        .route("create", web::post().to(create_run))
+        .route("update", web::post().to(update_run))
+        .route("delete", web::post().to(delete_run))
+        .route("restore", web::post().to(restore_run))
+        .route("log-metric", web::post().to(log_metric))
+        .route("log-parameter", web::post().to(log_param))
+        .route("set-tag", web::post().to(set_tag))
+        .route("delete-tag", web::post().to(delete_tag))
+        .route("log-batch", web::post().to(log_batch))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{SearchRunsRequest, SearchRunsResponse};
-    use crate::config::ServerConfig;
-    use crate::handlers::{get_api_endpoint, get_service};
+    use super::{
+        CreateRunRequest, CreateRunResponse, DeleteRunRequest, DeleteRunResponse, DeleteTagRequest,
+        LogBatchRequest, LogMetricRequest, LogParamRequest, RestoreRunRequest, RestoreRunResponse,
+        SearchRunsRequest, SearchRunsResponse, SetTagRequest, UpdateRunRequest, UpdateRunResponse,
+    };
+    use crate::entities::{Param, RunStatus, RunTag, ViewType};
+    use crate::handlers::{get_api_endpoint, init_test_service};
+    use crate::stores::tracking::error::ErrorResponse;
+    use actix_web::http::StatusCode;
     use actix_web::test;
-    use actix_web::{web, App};
 
     #[tokio::test]
     async fn test_search_runs() {
-        dotenv::from_filename(".env_dev").ok();
-        let server_config = ServerConfig::from_env();
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(server_config.clone()))
-                .service(get_service()),
-        )
-        .await;
+        let app = init_test_service().await;
         let req = test::TestRequest::post()
             .uri(get_api_endpoint("/runs/search").as_str())
             .set_json(&SearchRunsRequest {
                 experiment_ids: vec!["0".to_string()],
+                filter: None,
+                run_view_type: ViewType::ActiveOnly,
+                order_by: Some(vec!["attributes.start_time DESC".to_string()]),
+                max_results: None,
+                page_token: None,
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
@@ -419,6 +322,417 @@ mod tests {
         let body = test::read_body(resp).await;
         let result = String::from_utf8(body.to_vec()).unwrap();
         let search_runs_resp: SearchRunsResponse = serde_json::from_str(&result).unwrap();
-        assert!(search_runs_resp.runs.len() > 0);
+        assert!(!search_runs_resp.runs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_invalid_page_token() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/search").as_str())
+            .set_json(&SearchRunsRequest {
+                experiment_ids: vec!["0".to_string()],
+                filter: None,
+                run_view_type: ViewType::ActiveOnly,
+                order_by: None,
+                max_results: None,
+                page_token: Some("invalid".to_string()),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_invalid_filter() {
+        let app = init_test_service().await;
+        for (body, message) in [
+            (
+                serde_json::json!({"experiment_ids": ["0"], "filter": "metrics.loss ~ 1"}),
+                "Invalid filter: expected comparator after identifier `metrics.loss` at \
+                 column 14, found `~`",
+            ),
+            (
+                serde_json::json!({"experiment_ids": ["0"], "order_by": ["metrics.loss DOWN"]}),
+                "Invalid order by clause: expected ASC, DESC or end of input after identifier \
+                 `metrics.loss` at column 14, found `DOWN`",
+            ),
+        ] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint("/runs/search").as_str())
+                .set_json(&body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+            assert_eq!(error_resp.error_code, "INVALID_PARAMETER_VALUE");
+            assert_eq!(error_resp.message, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_deeply_nested_filter() {
+        let app = init_test_service().await;
+        let filter = format!("{}tags.a = 'x'{}", "(".repeat(10_000), ")".repeat(10_000));
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/search").as_str())
+            .set_json(serde_json::json!({"experiment_ids": ["0"], "filter": filter}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "INVALID_PARAMETER_VALUE");
+        assert!(error_resp
+            .message
+            .starts_with("Invalid filter: expected at most 100 levels of nesting at column 102"));
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_run() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::with_uri(
+            format!("{}?run_id=missing", get_api_endpoint("/runs/get")).as_str(),
+        )
+        .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "RESOURCE_DOES_NOT_EXIST");
+        assert_eq!(error_resp.message, "Run with id=missing not found");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_ids() {
+        let app = init_test_service().await;
+        for (endpoint, body) in [
+            (
+                "/runs/search",
+                serde_json::json!({"experiment_ids": ["0", "abc"]}),
+            ),
+            ("/runs/create", serde_json::json!({"experiment_id": "abc"})),
+        ] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint(endpoint).as_str())
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", endpoint);
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+            assert_eq!(error_resp.error_code, "INVALID_PARAMETER_VALUE");
+        }
+
+        let run_id = "not-a-run-id";
+        for (endpoint, body) in [
+            ("/runs/update", serde_json::json!({"run_id": run_id})),
+            ("/runs/delete", serde_json::json!({"run_id": run_id})),
+            ("/runs/restore", serde_json::json!({"run_id": run_id})),
+            (
+                "/runs/log-metric",
+                serde_json::json!({"run_id": run_id, "key": "m", "value": 1.0, "timestamp": 0}),
+            ),
+            (
+                "/runs/log-parameter",
+                serde_json::json!({"run_id": run_id, "key": "p", "value": "v"}),
+            ),
+            (
+                "/runs/set-tag",
+                serde_json::json!({"run_id": run_id, "key": "t", "value": "v"}),
+            ),
+            (
+                "/runs/delete-tag",
+                serde_json::json!({"run_id": run_id, "key": "t"}),
+            ),
+            ("/runs/log-batch", serde_json::json!({"run_id": run_id})),
+        ] {
+            let req = test::TestRequest::post()
+                .uri(get_api_endpoint(endpoint).as_str())
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", endpoint);
+            let body = test::read_body(resp).await;
+            let result = String::from_utf8(body.to_vec()).unwrap();
+            let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+            assert_eq!(error_resp.error_code, "RESOURCE_DOES_NOT_EXIST");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_run() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .set_json(&CreateRunRequest {
+                experiment_id: "0".to_string(),
+                user_id: Some("user".to_string()),
+                start_time: Some(1),
+                run_name: Some("run".to_string()),
+                tags: Some(vec![RunTag {
+                    key: "key".to_string(),
+                    value: "value".to_string(),
+                }]),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(create_run_resp.run.info.name, "run");
+        assert_eq!(create_run_resp.run.info.user_id, "user");
+        assert_eq!(create_run_resp.run.info.start_time, 1);
+        assert_eq!(create_run_resp.run.info.status, "RUNNING");
+        assert_eq!(create_run_resp.run.info.end_time, None);
+        assert_eq!(
+            create_run_resp.run.data.tags,
+            vec![
+                RunTag {
+                    key: "key".to_string(),
+                    value: "value".to_string(),
+                },
+                RunTag {
+                    key: "mlflow.runName".to_string(),
+                    value: "run".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_run() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .set_json(&CreateRunRequest {
+                experiment_id: "0".to_string(),
+                user_id: None,
+                start_time: None,
+                run_name: None,
+                tags: None,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/update").as_str())
+            .set_json(&UpdateRunRequest {
+                run_id: create_run_resp.run.info.run_id,
+                status: Some(RunStatus::Finished),
+                end_time: Some(100),
+                run_name: Some("new_name".to_string()),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let update_run_resp: UpdateRunResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(update_run_resp.run_info.status, "FINISHED");
+        assert_eq!(update_run_resp.run_info.end_time, Some(100));
+        assert_eq!(update_run_resp.run_info.name, "new_name");
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_restore_run() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .set_json(&CreateRunRequest {
+                experiment_id: "0".to_string(),
+                user_id: None,
+                start_time: None,
+                run_name: None,
+                tags: None,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/delete").as_str())
+            .set_json(&DeleteRunRequest {
+                run_id: create_run_resp.run.info.run_id,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let delete_run_resp: DeleteRunResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(delete_run_resp.run.info.lifecycle_stage, "deleted");
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/restore").as_str())
+            .set_json(&RestoreRunRequest {
+                run_id: delete_run_resp.run.info.run_id,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let restore_run_resp: RestoreRunResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(restore_run_resp.run.info.lifecycle_stage, "active");
+    }
+
+    #[tokio::test]
+    async fn test_log_run_data() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .set_json(&CreateRunRequest {
+                experiment_id: "0".to_string(),
+                user_id: None,
+                start_time: None,
+                run_name: None,
+                tags: None,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+        let run_id = create_run_resp.run.info.run_id;
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/log-metric").as_str())
+            .set_json(&LogMetricRequest {
+                run_id: run_id.clone(),
+                key: "m".to_string(),
+                value: 0.5,
+                timestamp: 1,
+                step: None,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/log-parameter").as_str())
+            .set_json(&LogParamRequest {
+                run_id: run_id.clone(),
+                key: "p".to_string(),
+                value: "a".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        // Params are immutable
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/log-parameter").as_str())
+            .set_json(&LogParamRequest {
+                run_id: run_id.clone(),
+                key: "p".to_string(),
+                value: "b".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_client_error());
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/set-tag").as_str())
+            .set_json(&SetTagRequest {
+                run_id: run_id.clone(),
+                key: "t".to_string(),
+                value: "v".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/delete-tag").as_str())
+            .set_json(&DeleteTagRequest {
+                run_id: run_id.clone(),
+                key: "t".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        // The tag is gone now
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/delete-tag").as_str())
+            .set_json(&DeleteTagRequest {
+                run_id: run_id.clone(),
+                key: "t".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let error_resp: ErrorResponse = serde_json::from_str(&result).unwrap();
+        assert_eq!(error_resp.error_code, "RESOURCE_DOES_NOT_EXIST");
+        assert_eq!(
+            error_resp.message,
+            format!("No tag with name: t in run with id {}", run_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_batch() {
+        let app = init_test_service().await;
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/create").as_str())
+            .set_json(&CreateRunRequest {
+                experiment_id: "0".to_string(),
+                user_id: None,
+                start_time: None,
+                run_name: None,
+                tags: None,
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let result = String::from_utf8(body.to_vec()).unwrap();
+        let create_run_resp: CreateRunResponse = serde_json::from_str(&result).unwrap();
+        let run_id = create_run_resp.run.info.run_id;
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/log-batch").as_str())
+            .set_json(serde_json::json!({
+                "run_id": run_id,
+                "metrics": [{"key": "m", "value": 1.0, "timestamp": 1}],
+                "params": [{"key": "p", "value": "a"}],
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::post()
+            .uri(get_api_endpoint("/runs/log-batch").as_str())
+            .set_json(&LogBatchRequest {
+                run_id: run_id.clone(),
+                metrics: vec![],
+                params: (0..101)
+                    .map(|i| Param {
+                        key: format!("p{}", i),
+                        value: "a".to_string(),
+                    })
+                    .collect(),
+                tags: vec![],
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
     }
 }
@@ -1,8 +1,10 @@
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use clap::Parser;
 use mlflow_rust_server::cli::Args;
-use mlflow_rust_server::config::ServerConfig;
-use mlflow_rust_server::handlers::get_service;
+use mlflow_rust_server::config::{PoolConfig, ServerConfig};
+use mlflow_rust_server::handlers::{get_service, json_config, query_config};
+use mlflow_rust_server::stores::tracking::get_store_from_server_config;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
@@ -10,16 +12,32 @@ async fn main() -> std::io::Result<()> {
     let server_config = ServerConfig {
         backend_store_uri: args.backend_store_uri,
         default_artifact_root: args.default_artifact_root,
+        pool: PoolConfig {
+            max_connections: args.pool_size,
+            acquire_timeout: Duration::from_secs(args.pool_acquire_timeout),
+            idle_timeout: match args.pool_idle_timeout {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            },
+        },
     };
 
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("debug"));
+    let store = get_store_from_server_config(&server_config)
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    let store_data = web::Data::from(store.clone());
     HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(server_config.clone()))
+            .app_data(store_data.clone())
+            .app_data(json_config())
+            .app_data(query_config())
             .wrap(Logger::default())
             .service(get_service())
     })
     .bind((args.host, args.port))?
     .run()
-    .await
+    .await?;
+    store.teardown().await;
+    Ok(())
 }
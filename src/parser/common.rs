@@ -1,8 +1,10 @@
 use nom::branch::alt;
-use nom::bytes::complete::{escaped, tag, take_while, take_while1};
-use nom::character::complete::{digit1, none_of};
-use nom::combinator::map;
+use nom::bytes::complete::{escaped, tag, take_while1};
+use nom::character::complete::{digit1, multispace0, none_of, one_of};
 use nom::combinator::opt;
+use nom::combinator::{map, map_res, recognize, verify};
+use nom::error::{Error, ErrorKind};
+use nom::multi::separated_list1;
 use nom::sequence::{delimited, pair, tuple};
 use nom::IResult;
 use std::str::FromStr;
@@ -21,50 +23,134 @@ pub struct Identifier {
     pub key: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Literal {
     String(String),
     Integer(i64),
     Float(f64),
+    List(Vec<Literal>),
+    /// The right-hand side of `IS NULL` and `IS NOT NULL`.
+    Null,
 }
 
 impl std::fmt::Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Literal::String(x) => write!(f, "'{}'", x),
+            Literal::String(x) => write!(f, "'{}'", x.replace('\\', "\\\\").replace('\'', "''")),
             Literal::Integer(x) => write!(f, "{}", x),
-            Literal::Float(x) => write!(f, "{}", x),
+            // Debug keeps the decimal point (`1.0`) and uses exponents for very large or small
+            // values, so the output parses back as a float
+            Literal::Float(x) => write!(f, "{:?}", x),
+            Literal::List(x) => write!(
+                f,
+                "({})",
+                x.iter()
+                    .map(|l| l.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Literal::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+/// Parses a string delimited by `quote`. Inside it, the quote is escaped by doubling it or with
+/// a backslash, and `\\` is a backslash. Other backslashes are kept as they are.
+fn quoted_string(quote: char) -> impl Fn(&str) -> IResult<&str, String> {
+    move |i: &str| {
+        let error = || nom::Err::Error(Error::new(i, ErrorKind::Char));
+        let mut chars = i.char_indices().peekable();
+        match chars.next() {
+            Some((_, c)) if c == quote => {}
+            _ => return Err(error()),
+        }
+        let mut value = String::new();
+        while let Some((index, c)) = chars.next() {
+            if c == quote {
+                if matches!(chars.peek(), Some((_, next)) if *next == quote) {
+                    chars.next();
+                    value.push(quote);
+                    continue;
+                }
+                return Ok((&i[index + c.len_utf8()..], value));
+            }
+            if c == '\\' {
+                if let Some((_, next)) = chars.next_if(|(_, next)| *next == quote || *next == '\\')
+                {
+                    value.push(next);
+                    continue;
+                }
+            }
+            value.push(c);
         }
+        // The string isn't terminated
+        Err(error())
     }
 }
 
+/// Parses a single- or double-quoted string, e.g. `'O''Brien'`, `'O\'Brien'` or `"O'Brien"`.
 pub fn string_literal(i: &str) -> IResult<&str, Literal> {
     map(
-        delimited(tag("'"), take_while(|c| c != '\''), tag("'")),
-        |s: &str| Literal::String(s.to_string()),
+        alt((quoted_string('\''), quoted_string('"'))),
+        Literal::String,
     )(i)
 }
 
 pub fn integer_literal(i: &str) -> IResult<&str, Literal> {
-    map(pair(opt(tag("-")), digit1), |tup| {
-        let intval = i64::from_str(tup.1).unwrap();
-        let sign = if (tup.0).is_some() { -1 } else { 1 };
-        Literal::Integer(sign * intval)
+    map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| {
+        i64::from_str(s).map(Literal::Integer)
     })(i)
 }
 
+fn exponent(i: &str) -> IResult<&str, &str> {
+    recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)))(i)
+}
+
+/// Parses a float such as `0.5`, `-1.5e3`, `1e-5`, `.5` or `1.`. A number without a decimal
+/// point or an exponent is an integer.
 pub fn float_literal(i: &str) -> IResult<&str, Literal> {
-    map(tuple((opt(tag("-")), digit1, tag("."), digit1)), |tup| {
-        let floatval = f64::from_str(format!("{}.{}", tup.1, tup.3).as_str()).unwrap();
-        let sign = if (tup.0).is_some() { -1.0 } else { 1.0 };
-        Literal::Float(sign * floatval)
-    })(i)
+    map(
+        verify(
+            map_res(
+                recognize(pair(
+                    opt(tag("-")),
+                    alt((
+                        recognize(tuple((digit1, tag("."), opt(digit1), opt(exponent)))),
+                        recognize(tuple((tag("."), digit1, opt(exponent)))),
+                        recognize(pair(digit1, exponent)),
+                    )),
+                )),
+                f64::from_str,
+            ),
+            |f: &f64| f.is_finite(),
+        ),
+        Literal::Float,
+    )(i)
 }
 
-pub fn literal(i: &str) -> IResult<&str, Literal> {
+fn scalar_literal(i: &str) -> IResult<&str, Literal> {
     alt((float_literal, integer_literal, string_literal))(i)
 }
 
+/// Parses a parenthesized, comma-separated list of scalar literals, e.g. `('a', 'b')`.
+pub fn list_literal(i: &str) -> IResult<&str, Literal> {
+    map(
+        delimited(
+            pair(tag("("), multispace0),
+            separated_list1(
+                delimited(multispace0, tag(","), multispace0),
+                scalar_literal,
+            ),
+            pair(multispace0, tag(")")),
+        ),
+        Literal::List,
+    )(i)
+}
+
+pub fn literal(i: &str) -> IResult<&str, Literal> {
+    alt((scalar_literal, list_literal))(i)
+}
+
 fn alphanumeric_or_underscore(i: &str) -> IResult<&str, &str> {
     take_while1(|c: char| c.is_alphanumeric() || c == '_')(i)
 }
@@ -84,9 +170,13 @@ pub fn identifier(i: &str) -> IResult<&str, Identifier> {
         tuple((
             opt(alt((
                 tag("attribute."),
+                tag("attributes."),
                 tag("param."),
+                tag("params."),
                 tag("metric."),
+                tag("metrics."),
                 tag("tag."),
+                tag("tags."),
             ))),
             alt((
                 alphanumeric_or_underscore,
@@ -101,10 +191,10 @@ pub fn identifier(i: &str) -> IResult<&str, Identifier> {
             };
             Identifier {
                 entity: match entity.as_str() {
-                    "attribute" => Entity::Attribute,
-                    "param" => Entity::Param,
-                    "metric" => Entity::Metric,
-                    "tag" => Entity::Tag,
+                    "attribute" | "attributes" => Entity::Attribute,
+                    "param" | "params" => Entity::Param,
+                    "metric" | "metrics" => Entity::Metric,
+                    "tag" | "tags" => Entity::Tag,
                     _ => unreachable!(),
                 },
                 key: tup.1.to_string(),
@@ -115,9 +205,12 @@ pub fn identifier(i: &str) -> IResult<&str, Identifier> {
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::{
         alphanumeric_or_underscore, backtick_delimited, double_quote_delimited, float_literal,
-        identifier, integer_literal, literal, string_literal, Entity, Identifier, Literal,
+        identifier, integer_literal, list_literal, literal, string_literal, Entity, Identifier,
+        Literal,
     };
 
     #[test]
@@ -162,6 +255,27 @@ mod tests {
 
         let res = string_literal("''").unwrap();
         assert_eq!(res, ("", Literal::String("".to_string())));
+
+        let o_brien = Literal::String("O'Brien".to_string());
+        assert_eq!(string_literal("'O''Brien'").unwrap(), ("", o_brien.clone()));
+        assert_eq!(
+            string_literal(r"'O\'Brien'").unwrap(),
+            ("", o_brien.clone())
+        );
+        assert_eq!(string_literal("\"O'Brien\"").unwrap(), ("", o_brien));
+
+        let res = string_literal(r#""say ""hi"" \"bye\"""#).unwrap();
+        assert_eq!(res, ("", Literal::String(r#"say "hi" "bye""#.to_string())));
+
+        let res = string_literal(r"'C:\dir\\'").unwrap();
+        assert_eq!(res, ("", Literal::String(r"C:\dir\".to_string())));
+
+        let res = string_literal("'a' AND b = 'c'").unwrap();
+        assert_eq!(res, (" AND b = 'c'", Literal::String("a".to_string())));
+
+        assert!(string_literal("'unterminated").is_err());
+        assert!(string_literal(r"'unterminated\'").is_err());
+        assert!(string_literal("\"mixed'").is_err());
     }
 
     #[test]
@@ -174,6 +288,8 @@ mod tests {
 
         let res = integer_literal("-1").unwrap();
         assert_eq!(res, ("", Literal::Integer(-1)));
+
+        assert!(integer_literal("99999999999999999999").is_err());
     }
 
     #[test]
@@ -186,6 +302,28 @@ mod tests {
 
         let res = float_literal("-0.1").unwrap();
         assert_eq!(res, ("", Literal::Float(-0.1)));
+
+        for (input, expected) in [
+            ("1e-5", 1e-5),
+            ("1E5", 1e5),
+            ("-2.5e+3", -2.5e3),
+            (".5", 0.5),
+            ("-.5e1", -5.0),
+            ("1.", 1.0),
+            ("1.e2", 100.0),
+        ] {
+            assert_eq!(
+                float_literal(input).unwrap(),
+                ("", Literal::Float(expected)),
+                "input: {}",
+                input
+            );
+        }
+
+        assert!(float_literal("1").is_err());
+        assert!(float_literal(".").is_err());
+        assert!(float_literal("1e999").is_err());
+        assert!(float_literal("1e").is_err());
     }
 
     #[test]
@@ -196,6 +334,46 @@ mod tests {
         assert_eq!(res, ("", Literal::Float(1.0)));
         let res = literal("'string'").unwrap();
         assert_eq!(res, ("", Literal::String("string".to_string())));
+        let res = literal("1e3").unwrap();
+        assert_eq!(res, ("", Literal::Float(1000.0)));
+        let res = literal("\"string\"").unwrap();
+        assert_eq!(res, ("", Literal::String("string".to_string())));
+        let res = literal("('a')").unwrap();
+        assert_eq!(
+            res,
+            ("", Literal::List(vec![Literal::String("a".to_string())]))
+        );
+    }
+
+    #[test]
+    fn test_list_literal() {
+        let res = list_literal("('a', 'b')").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Literal::List(vec![
+                    Literal::String("a".to_string()),
+                    Literal::String("b".to_string())
+                ])
+            )
+        );
+        let res = list_literal("( 1,2.5 ,-3 )").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Literal::List(vec![
+                    Literal::Integer(1),
+                    Literal::Float(2.5),
+                    Literal::Integer(-3)
+                ])
+            )
+        );
+        assert_eq!(res.1.to_string(), "(1, 2.5, -3)");
+        assert!(list_literal("()").is_err());
+        assert!(list_literal("(('a'))").is_err());
+        assert!(list_literal("('a',)").is_err());
     }
 
     #[test]
@@ -272,4 +450,92 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_identifier_plural_prefix() {
+        for (prefix, entity) in [
+            ("attributes", Entity::Attribute),
+            ("params", Entity::Param),
+            ("metrics", Entity::Metric),
+            ("tags", Entity::Tag),
+        ] {
+            let input = format!("{}.key", prefix);
+            let res = identifier(input.as_str()).unwrap();
+            assert_eq!(
+                res,
+                (
+                    "",
+                    Identifier {
+                        entity,
+                        key: "key".to_string()
+                    }
+                )
+            );
+        }
+    }
+
+    /// Strings made of characters that need quoting or escaping, among a few others.
+    const STRING_PATTERN: &str = "[aZ0 %_'\"\\\\`(),é日]{0,12}";
+
+    fn scalar_literal_strategy() -> impl Strategy<Value = Literal> {
+        prop_oneof![
+            STRING_PATTERN.prop_map(Literal::String),
+            any::<i64>().prop_map(Literal::Integer),
+            (-1e6..1e6).prop_map(Literal::Float),
+            // Any finite float, including tiny and huge ones
+            any::<f64>()
+                .prop_filter("finite", |f| f.is_finite())
+                .prop_map(Literal::Float),
+        ]
+    }
+
+    fn literal_strategy() -> impl Strategy<Value = Literal> {
+        prop_oneof![
+            4 => scalar_literal_strategy(),
+            1 => prop::collection::vec(scalar_literal_strategy(), 1..4).prop_map(Literal::List),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_literal_display_round_trip(expected in literal_strategy()) {
+            let displayed = expected.to_string();
+            prop_assert_eq!(literal(&displayed), Ok(("", expected)), "displayed: {}", displayed);
+        }
+
+        #[test]
+        fn test_string_literal_escapes(value in STRING_PATTERN) {
+            for quote in ['\'', '"'] {
+                let escaped = value.replace('\\', "\\\\");
+                for escaped_quote in [format!("{}{}", quote, quote), format!("\\{}", quote)] {
+                    let input = format!(
+                        "{}{}{}",
+                        quote,
+                        escaped.replace(quote, &escaped_quote),
+                        quote
+                    );
+                    prop_assert_eq!(
+                        string_literal(&input),
+                        Ok(("", Literal::String(value.clone()))),
+                        "input: {}",
+                        input
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_float_literal_forms(
+            input in prop_oneof![
+                "-?[0-9]{1,5}\\.[0-9]{0,5}([eE][+-]?[0-9]{1,2})?",
+                "-?\\.[0-9]{1,5}([eE][+-]?[0-9]{1,2})?",
+                "-?[0-9]{1,5}[eE][+-]?[0-9]{1,2}",
+            ]
+        ) {
+            prop_assert_eq!(
+                float_literal(&input),
+                Ok(("", Literal::Float(input.parse().unwrap())))
+            );
+        }
+    }
 }
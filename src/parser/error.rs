@@ -0,0 +1,103 @@
+/// Describes where and why a filter or order_by clause failed to parse.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// The byte offset of the offending token in the input.
+    pub offset: usize,
+    /// The column of the offending token, starting at 1.
+    pub column: usize,
+    /// The offending token, or `None` at the end of the input.
+    pub token: Option<String>,
+    /// What was expected instead, e.g. "comparator after identifier `metrics.loss`".
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(input: &str, offset: usize, expected: String) -> ParseError {
+        ParseError {
+            offset,
+            column: input[..offset].chars().count() + 1,
+            token: input[offset..]
+                .split_whitespace()
+                .next()
+                .map(|t| t.to_string()),
+            expected,
+        }
+    }
+}
+
+/// The error of the filter and order_by grammars: the input left where parsing stopped and what
+/// was expected there.
+#[derive(Debug, PartialEq)]
+pub struct SyntaxError<'a> {
+    pub input: &'a str,
+    pub expected: String,
+}
+
+pub type SyntaxResult<'a, O> = nom::IResult<&'a str, O, SyntaxError<'a>>;
+
+impl<'a> SyntaxError<'a> {
+    pub fn new(input: &'a str, expected: impl Into<String>) -> SyntaxError<'a> {
+        SyntaxError {
+            input,
+            expected: expected.into(),
+        }
+    }
+
+    /// An error that lets the parser backtrack and try another branch.
+    pub fn error(input: &'a str, expected: impl Into<String>) -> nom::Err<SyntaxError<'a>> {
+        nom::Err::Error(SyntaxError::new(input, expected))
+    }
+
+    /// An error the parser doesn't recover from, once the input can't be anything else.
+    pub fn failure(input: &'a str, expected: impl Into<String>) -> nom::Err<SyntaxError<'a>> {
+        nom::Err::Failure(SyntaxError::new(input, expected))
+    }
+
+    /// Locates the error in `input`, the whole text that was parsed.
+    pub fn into_parse_error(self, input: &str) -> ParseError {
+        let rest = self.input.trim_start();
+        ParseError::new(input, input.len() - rest.len(), self.expected)
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for SyntaxError<'a> {
+    fn from_error_kind(input: &'a str, kind: nom::error::ErrorKind) -> Self {
+        SyntaxError::new(input, kind.description())
+    }
+
+    fn append(_: &'a str, _: nom::error::ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "expected {} at column {}, ", self.expected, self.column)?;
+        match &self.token {
+            Some(token) => write!(f, "found `{}`", token),
+            None => write!(f, "found end of input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseError;
+
+    #[test]
+    fn test_parse_error() {
+        let e = ParseError::new("metrics.loss ~ 1", 13, "comparator".to_string());
+        assert_eq!(e.column, 14);
+        assert_eq!(e.token, Some("~".to_string()));
+        assert_eq!(e.to_string(), "expected comparator at column 14, found `~`");
+
+        // The column counts characters, not bytes
+        let e = ParseError::new("é =", 4, "value".to_string());
+        assert_eq!(e.column, 4);
+        assert_eq!(e.token, None);
+        assert_eq!(
+            e.to_string(),
+            "expected value at column 4, found end of input"
+        );
+    }
+}
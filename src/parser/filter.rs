@@ -1,13 +1,16 @@
 use crate::parser::common::{identifier, literal, Identifier, Literal};
+use crate::parser::error::{ParseError, SyntaxError, SyntaxResult};
 use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case};
-use nom::character::complete::multispace0;
-use nom::combinator::map;
-use nom::multi::separated_list0;
-use nom::sequence::{delimited, tuple};
-use nom::IResult;
+use nom::character::complete::{multispace0, multispace1};
+use nom::combinator::{cut, eof, map, opt, peek, value};
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+use nom::{Finish, IResult};
 
-#[derive(Debug, PartialEq)]
+/// How deeply parentheses and `NOT`s may be nested in a filter.
+pub const MAX_FILTER_DEPTH: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Comparator {
     Equal,
     NotEqual,
@@ -17,6 +20,10 @@ pub enum Comparator {
     LessThanOrEqual,
     Like,
     ILike,
+    In,
+    NotIn,
+    IsNull,
+    IsNotNull,
 }
 
 impl std::fmt::Display for Comparator {
@@ -30,6 +37,11 @@ impl std::fmt::Display for Comparator {
             Comparator::LessThanOrEqual => write!(f, "<="),
             Comparator::Like => write!(f, "LIKE"),
             Comparator::ILike => write!(f, "ILIKE"),
+            Comparator::In => write!(f, "IN"),
+            Comparator::NotIn => write!(f, "NOT IN"),
+            // The right-hand side is `Literal::Null`
+            Comparator::IsNull => write!(f, "IS"),
+            Comparator::IsNotNull => write!(f, "IS NOT"),
         }
     }
 }
@@ -37,6 +49,7 @@ impl std::fmt::Display for Comparator {
 #[derive(Debug, PartialEq)]
 pub enum LogicalOperator {
     And,
+    Or,
 }
 
 #[derive(Debug, PartialEq)]
@@ -46,66 +59,206 @@ pub struct Comparison {
     pub right: Literal,
 }
 
+/// A parsed filter. `NOT` binds tighter than `AND`, which binds tighter than `OR`. Chains of
+/// `AND`s and `OR`s are kept flat, so the tree is only as deep as the filter is nested.
+#[derive(Debug, PartialEq)]
+pub enum Expression {
+    Comparison(Comparison),
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Not(Box<Expression>),
+}
+
+impl Expression {
+    /// Returns the comparisons in the expression, from left to right.
+    pub fn comparisons(&self) -> Vec<&Comparison> {
+        match self {
+            Expression::Comparison(c) => vec![c],
+            Expression::And(operands) | Expression::Or(operands) => {
+                operands.iter().flat_map(|e| e.comparisons()).collect()
+            }
+            Expression::Not(e) => e.comparisons(),
+        }
+    }
+}
+
 pub fn comparator(i: &str) -> IResult<&str, Comparator> {
-    map(
-        delimited(
-            multispace0,
-            alt((
-                tag("!="),
-                tag("<="),
-                tag(">="),
-                tag("<"),
-                tag(">"),
-                tag("="),
-                tag_no_case("like"),
-                tag_no_case("ilike"),
-            )),
-            multispace0,
-        ),
-        |c: &str| match c.to_lowercase().as_str() {
-            "=" => Comparator::Equal,
-            "!=" => Comparator::NotEqual,
-            "<" => Comparator::LessThan,
-            "<=" => Comparator::LessThanOrEqual,
-            ">" => Comparator::GreaterThan,
-            ">=" => Comparator::GreaterThanOrEqual,
-            "like" => Comparator::Like,
-            "ilike" => Comparator::ILike,
-            _ => unreachable!(),
-        },
+    delimited(
+        multispace0,
+        alt((
+            value(Comparator::NotEqual, tag("!=")),
+            value(Comparator::LessThanOrEqual, tag("<=")),
+            value(Comparator::GreaterThanOrEqual, tag(">=")),
+            value(Comparator::LessThan, tag("<")),
+            value(Comparator::GreaterThan, tag(">")),
+            value(Comparator::Equal, tag("=")),
+            value(Comparator::Like, tag_no_case("like")),
+            value(Comparator::ILike, tag_no_case("ilike")),
+            value(Comparator::In, tag_no_case("in")),
+            value(
+                Comparator::NotIn,
+                tuple((tag_no_case("not"), multispace1, tag_no_case("in"))),
+            ),
+        )),
+        multispace0,
     )(i)
 }
 
-pub fn comparison_op(i: &str) -> IResult<&str, Comparison> {
-    map(
-        tuple((identifier, comparator, literal)),
-        |tup: (Identifier, Comparator, Literal)| Comparison {
-            operator: tup.1,
-            left: tup.0,
-            right: tup.2,
-        },
+/// Parses `IS NULL` or `IS NOT NULL`.
+pub fn null_comparator(i: &str) -> IResult<&str, Comparator> {
+    preceded(
+        pair(multispace0, tag_no_case("is")),
+        alt((
+            value(Comparator::IsNull, pair(multispace1, tag_no_case("null"))),
+            value(
+                Comparator::IsNotNull,
+                tuple((
+                    multispace1,
+                    tag_no_case("not"),
+                    multispace1,
+                    tag_no_case("null"),
+                )),
+            ),
+        )),
     )(i)
 }
 
-pub fn logical_operator(i: &str) -> IResult<&str, LogicalOperator> {
-    map(
-        delimited(multispace0, alt((tag_no_case("and"),)), multispace0),
-        |c: &str| match c.to_lowercase().as_str() {
-            "and" => LogicalOperator::And,
-            _ => panic!("Unknown logical operator"),
+/// Parses a comparison. Once the identifier is parsed, the rest of the comparison must follow.
+pub fn comparison_op(i: &str) -> SyntaxResult<'_, Comparison> {
+    let (rest, left) = identifier(i).map_err(|_| SyntaxError::error(i, "identifier"))?;
+    let key = &i[..i.len() - rest.len()];
+    if let Ok((rest, operator)) = null_comparator(rest) {
+        return Ok((
+            rest,
+            Comparison {
+                operator,
+                left,
+                right: Literal::Null,
+            },
+        ));
+    }
+    let (value, operator) = comparator(rest).map_err(|_| {
+        SyntaxError::failure(rest, format!("comparator after identifier `{}`", key))
+    })?;
+    let (rest, right) = literal(value)
+        .map_err(|_| SyntaxError::failure(value, format!("value after `{} {}`", key, operator)))?;
+    Ok((
+        rest,
+        Comparison {
+            operator,
+            left,
+            right,
         },
-    )(i)
+    ))
+}
+
+/// Matches a keyword that isn't the start of a longer word, so that keys such as `notes` or
+/// `origin` are still parsed as identifiers.
+fn keyword<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    delimited(
+        multispace0,
+        terminated(tag_no_case(name), peek(alt((multispace1, tag("("), eof)))),
+        multispace0,
+    )
+}
+
+pub fn logical_operator(i: &str) -> IResult<&str, LogicalOperator> {
+    alt((and_operator, or_operator))(i)
+}
+
+fn and_operator(i: &str) -> IResult<&str, LogicalOperator> {
+    map(keyword("and"), |_| LogicalOperator::And)(i)
 }
 
-pub fn parse_filter(i: &str) -> IResult<&str, Vec<Comparison>> {
-    separated_list0(logical_operator, comparison_op)(i)
+fn or_operator(i: &str) -> IResult<&str, LogicalOperator> {
+    map(keyword("or"), |_| LogicalOperator::Or)(i)
+}
+
+/// Parses an operand of `AND`, nested `depth` levels deep.
+fn not_expression(i: &str, depth: usize) -> SyntaxResult<'_, Expression> {
+    if depth > MAX_FILTER_DEPTH {
+        return Err(SyntaxError::failure(
+            i,
+            format!("at most {} levels of nesting", MAX_FILTER_DEPTH),
+        ));
+    }
+    if let Ok((rest, _)) = keyword("not")(i) {
+        return match not_expression(rest, depth + 1) {
+            Ok((rest, e)) => Ok((rest, Expression::Not(Box::new(e)))),
+            // `not` may also be a key, as in `not = 'a'`
+            Err(e) => match comparison_op(i) {
+                Ok((rest, c)) => Ok((rest, Expression::Comparison(c))),
+                Err(_) => Err(e),
+            },
+        };
+    }
+    if let Some(rest) = i.strip_prefix('(') {
+        let (rest, e) = cut(|i| or_expression(i, depth + 1))(rest.trim_start())?;
+        let rest = rest.trim_start();
+        let rest = rest
+            .strip_prefix(')')
+            .ok_or_else(|| SyntaxError::failure(rest, "AND, OR or `)`"))?;
+        return Ok((rest, e));
+    }
+    let (rest, c) = comparison_op(i)?;
+    Ok((rest, Expression::Comparison(c)))
+}
+
+fn and_expression(i: &str, depth: usize) -> SyntaxResult<'_, Expression> {
+    let (mut i, first) = not_expression(i, depth)?;
+    let mut operands = vec![first];
+    while let Ok((rest, _)) = and_operator(i) {
+        let (rest, operand) = cut(|i| not_expression(i, depth))(rest)?;
+        operands.push(operand);
+        i = rest;
+    }
+    if operands.len() == 1 {
+        return Ok((i, operands.remove(0)));
+    }
+    Ok((i, Expression::And(operands)))
+}
+
+fn or_expression(i: &str, depth: usize) -> SyntaxResult<'_, Expression> {
+    let (mut i, first) = and_expression(i, depth)?;
+    let mut operands = vec![first];
+    while let Ok((rest, _)) = or_operator(i) {
+        let (rest, operand) = cut(|i| and_expression(i, depth))(rest)?;
+        operands.push(operand);
+        i = rest;
+    }
+    if operands.len() == 1 {
+        return Ok((i, operands.remove(0)));
+    }
+    Ok((i, Expression::Or(operands)))
+}
+
+/// Parses a filter into an expression tree. An empty filter is `None`.
+pub fn parse_filter(i: &str) -> SyntaxResult<'_, Option<Expression>> {
+    opt(|i| or_expression(i, 0))(i)
+}
+
+/// Parses a whole filter, surrounding whitespace included. If it doesn't parse, the error
+/// points at the first token that doesn't fit the grammar.
+pub fn parse_filter_complete(input: &str) -> Result<Option<Expression>, ParseError> {
+    let i = input.trim_start();
+    if i.is_empty() {
+        return Ok(None);
+    }
+    let (rest, expression) = or_expression(i, 0)
+        .finish()
+        .map_err(|e| e.into_parse_error(input))?;
+    if !rest.trim().is_empty() {
+        return Err(SyntaxError::new(rest, "AND, OR or end of input").into_parse_error(input));
+    }
+    Ok(Some(expression))
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        comparator, comparison_op, logical_operator, parse_filter, Comparator, Comparison,
-        Identifier, Literal, LogicalOperator,
+        comparator, comparison_op, logical_operator, parse_filter, parse_filter_complete,
+        Comparator, Comparison, Expression, Identifier, Literal, LogicalOperator, ParseError,
+        SyntaxError, MAX_FILTER_DEPTH,
     };
     use crate::parser::common::Entity;
 
@@ -149,6 +302,17 @@ mod tests {
 
         let res = comparator("ILIKE").unwrap();
         assert_eq!(res, ("", Comparator::ILike));
+
+        let res = comparator(" IN ").unwrap();
+        assert_eq!(res, ("", Comparator::In));
+
+        let res = comparator("not in").unwrap();
+        assert_eq!(res, ("", Comparator::NotIn));
+
+        let res = comparator(" NOT  IN ").unwrap();
+        assert_eq!(res, ("", Comparator::NotIn));
+
+        assert!(comparator("NOTIN").is_err());
     }
 
     #[test]
@@ -190,6 +354,62 @@ mod tests {
 
         let res = comparison_op("attribute.`k e y` = 'value'").unwrap();
         assert_eq!(res, expected_contains_spaces);
+
+        let res = comparison_op("tags.owner IS NULL").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Comparison {
+                    operator: Comparator::IsNull,
+                    left: Identifier {
+                        entity: Entity::Tag,
+                        key: "owner".to_string(),
+                    },
+                    right: Literal::Null,
+                },
+            )
+        );
+        assert_eq!(res.1.right.to_string(), "NULL");
+
+        let res = comparison_op("metrics.`m`is  not null").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Comparison {
+                    operator: Comparator::IsNotNull,
+                    left: Identifier {
+                        entity: Entity::Metric,
+                        key: "m".to_string(),
+                    },
+                    right: Literal::Null,
+                },
+            )
+        );
+
+        assert!(comparison_op("tags.owner ISNULL").is_err());
+        assert!(comparison_op("tags.owner IS 'a'").is_err());
+        assert!(comparison_op("tags.owner = NULL").is_err());
+
+        let res = comparison_op("attributes.run_id NOT IN ('a','b')").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Comparison {
+                    operator: Comparator::NotIn,
+                    left: Identifier {
+                        entity: Entity::Attribute,
+                        key: "run_id".to_string(),
+                    },
+                    right: Literal::List(vec![
+                        Literal::String("a".to_string()),
+                        Literal::String("b".to_string())
+                    ]),
+                },
+            )
+        );
     }
 
     #[test]
@@ -211,6 +431,44 @@ mod tests {
 
         let res = logical_operator("AnD").unwrap();
         assert_eq!(res, ("", LogicalOperator::And));
+
+        let res = logical_operator(" or ").unwrap();
+        assert_eq!(res, ("", LogicalOperator::Or));
+
+        let res = logical_operator("OR").unwrap();
+        assert_eq!(res, ("", LogicalOperator::Or));
+    }
+
+    fn comparison(entity: Entity, key: &str, operator: Comparator, right: Literal) -> Expression {
+        Expression::Comparison(Comparison {
+            operator,
+            left: Identifier {
+                entity,
+                key: key.to_string(),
+            },
+            right,
+        })
+    }
+
+    fn and(operands: Vec<Expression>) -> Expression {
+        Expression::And(operands)
+    }
+
+    fn or(operands: Vec<Expression>) -> Expression {
+        Expression::Or(operands)
+    }
+
+    fn not(e: Expression) -> Expression {
+        Expression::Not(Box::new(e))
+    }
+
+    fn team(value: &str) -> Expression {
+        comparison(
+            Entity::Tag,
+            "team",
+            Comparator::Equal,
+            Literal::String(value.to_string()),
+        )
     }
 
     #[test]
@@ -218,53 +476,212 @@ mod tests {
         let res = parse_filter("attribute.key = 'value'").unwrap();
         let expected = (
             "",
-            vec![Comparison {
-                operator: Comparator::Equal,
-                left: Identifier {
-                    entity: Entity::Attribute,
-                    key: "key".to_string(),
-                },
-                right: Literal::String("value".to_string()),
-            }],
+            Some(comparison(
+                Entity::Attribute,
+                "key",
+                Comparator::Equal,
+                Literal::String("value".to_string()),
+            )),
         );
         assert_eq!(res, expected);
 
         let res = parse_filter("key = 'value'").unwrap();
-        let expected = (
-            "",
-            vec![Comparison {
-                operator: Comparator::Equal,
-                left: Identifier {
-                    entity: Entity::Attribute,
-                    key: "key".to_string(),
-                },
-                right: Literal::String("value".to_string()),
-            }],
-        );
         assert_eq!(res, expected);
 
         let res = parse_filter("attribute.key = 'value' AND metric.key > 0.5").unwrap();
         let expected = (
             "",
-            vec![
-                Comparison {
-                    operator: Comparator::Equal,
-                    left: Identifier {
-                        entity: Entity::Attribute,
-                        key: "key".to_string(),
-                    },
-                    right: Literal::String("value".to_string()),
-                },
-                Comparison {
-                    operator: Comparator::GreaterThan,
-                    left: Identifier {
-                        entity: Entity::Metric,
-                        key: "key".to_string(),
-                    },
-                    right: Literal::Float(0.5),
-                },
-            ],
+            Some(and(vec![
+                comparison(
+                    Entity::Attribute,
+                    "key",
+                    Comparator::Equal,
+                    Literal::String("value".to_string()),
+                ),
+                comparison(
+                    Entity::Metric,
+                    "key",
+                    Comparator::GreaterThan,
+                    Literal::Float(0.5),
+                ),
+            ])),
         );
         assert_eq!(res, expected);
+
+        assert_eq!(parse_filter("").unwrap(), ("", None));
+    }
+
+    #[test]
+    fn test_parse_or() {
+        let res = parse_filter("tags.team = 'a' OR tags.team = 'b'").unwrap();
+        assert_eq!(res, ("", Some(or(vec![team("a"), team("b")]))));
+
+        let res = parse_filter("tags.team = 'a' or tags.team = 'b' OR tags.team = 'c'").unwrap();
+        assert_eq!(res, ("", Some(or(vec![team("a"), team("b"), team("c")]))));
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        // AND binds tighter than OR
+        let res = parse_filter("tags.team = 'a' OR tags.team = 'b' AND tags.team = 'c'").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Some(or(vec![team("a"), and(vec![team("b"), team("c")])]))
+            )
+        );
+
+        let res = parse_filter("tags.team = 'a' AND tags.team = 'b' OR tags.team = 'c'").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Some(or(vec![and(vec![team("a"), team("b")]), team("c")]))
+            )
+        );
+
+        // NOT binds tighter than AND
+        let res = parse_filter("NOT tags.team = 'a' AND tags.team = 'b'").unwrap();
+        assert_eq!(res, ("", Some(and(vec![not(team("a")), team("b")]))));
+    }
+
+    #[test]
+    fn test_parse_parentheses() {
+        let res = parse_filter("(tags.team = 'a' OR tags.team = 'b') AND tags.team = 'c'").unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                Some(and(vec![or(vec![team("a"), team("b")]), team("c")]))
+            )
+        );
+
+        let res = parse_filter("( ( tags.team = 'a' ) )").unwrap();
+        assert_eq!(res, ("", Some(team("a"))));
+
+        let res = parse_filter("not(tags.team = 'a' OR tags.team = 'b')").unwrap();
+        assert_eq!(res, ("", Some(not(or(vec![team("a"), team("b")])))));
+
+        let res = parse_filter("NOT NOT tags.team = 'a'").unwrap();
+        assert_eq!(res, ("", Some(not(not(team("a"))))));
+    }
+
+    #[test]
+    fn test_parse_depth() {
+        let nested =
+            |depth: usize| format!("{}tags.team = 'a'{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(
+            parse_filter_complete(&nested(MAX_FILTER_DEPTH)).unwrap(),
+            Some(team("a"))
+        );
+        let e = parse_filter_complete(&nested(MAX_FILTER_DEPTH + 1)).unwrap_err();
+        assert_eq!(e.offset, MAX_FILTER_DEPTH + 1);
+        assert_eq!(e.expected, "at most 100 levels of nesting");
+
+        let e = parse_filter_complete(&format!("{}tags.team = 'a'", "NOT ".repeat(10_000)))
+            .unwrap_err();
+        assert_eq!(e.offset, (MAX_FILTER_DEPTH + 1) * 4);
+
+        // Long chains don't nest
+        let chain = vec!["tags.team = 'a'"; 10_000].join(" AND ");
+        let expression = parse_filter_complete(&chain).unwrap().unwrap();
+        assert_eq!(expression.comparisons().len(), 10_000);
+    }
+
+    #[test]
+    fn test_parse_keywords_in_keys() {
+        let res = parse_filter("notes = 'a' AND order = 'b' OR android = 'c'").unwrap();
+        let string = |s: &str| Literal::String(s.to_string());
+        assert_eq!(
+            res,
+            (
+                "",
+                Some(or(vec![
+                    and(vec![
+                        comparison(Entity::Attribute, "notes", Comparator::Equal, string("a")),
+                        comparison(Entity::Attribute, "order", Comparator::Equal, string("b")),
+                    ]),
+                    comparison(Entity::Attribute, "android", Comparator::Equal, string("c")),
+                ]))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_incomplete() {
+        // Nothing else can follow an operator or an opening parenthesis
+        assert_eq!(
+            parse_filter("tags.team = 'a' OR"),
+            Err(nom::Err::Failure(SyntaxError::new("", "identifier")))
+        );
+        assert_eq!(
+            parse_filter("(tags.team = 'a'"),
+            Err(nom::Err::Failure(SyntaxError::new("", "AND, OR or `)`")))
+        );
+
+        let (remaining, _) = parse_filter("tags.team = 'a')").unwrap();
+        assert_eq!(remaining, ")");
+
+        let (remaining, _) = parse_filter("NOT").unwrap();
+        assert_eq!(remaining, "NOT");
+
+        // A missing operator isn't mistaken for a key starting with one
+        let (remaining, _) = parse_filter("tags.team = 'a' order = 'b'").unwrap();
+        assert_eq!(remaining, " order = 'b'");
+    }
+
+    #[test]
+    fn test_parse_filter_complete() {
+        assert_eq!(parse_filter_complete("").unwrap(), None);
+        assert_eq!(
+            parse_filter_complete("  tags.team = 'a'  ").unwrap(),
+            Some(team("a"))
+        );
+        assert!(parse_filter_complete("not = 'a'").is_ok());
+
+        for (filter, offset, token, expected) in [
+            (
+                "metrics.loss ~ 1",
+                13,
+                Some("~"),
+                "comparator after identifier `metrics.loss`",
+            ),
+            ("metrics.loss >", 14, None, "value after `metrics.loss >`"),
+            (
+                "tags.a = 'unterminated",
+                9,
+                Some("'unterminated"),
+                "value after `tags.a =`",
+            ),
+            ("tags.a = 'x' AND", 16, None, "identifier"),
+            ("= 'x'", 0, Some("="), "identifier"),
+            (
+                "tags.a = 'x' tags.b = 'y'",
+                13,
+                Some("tags.b"),
+                "AND, OR or end of input",
+            ),
+            ("(tags.a = 'x'", 13, None, "AND, OR or `)`"),
+            (
+                "NOT (params.p = )",
+                16,
+                Some(")"),
+                "value after `params.p =`",
+            ),
+        ] {
+            let e = parse_filter_complete(filter).unwrap_err();
+            assert_eq!(
+                e,
+                ParseError {
+                    offset,
+                    column: offset + 1,
+                    token: token.map(|t| t.to_string()),
+                    expected: expected.to_string(),
+                },
+                "filter: {}",
+                filter
+            );
+        }
     }
 }
@@ -1,3 +1,4 @@
 pub mod common;
+pub mod error;
 pub mod filter;
 pub mod order_by;
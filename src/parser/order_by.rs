@@ -1,11 +1,11 @@
 use crate::parser::common::{identifier, Identifier};
+use crate::parser::error::{ParseError, SyntaxError, SyntaxResult};
 use nom::branch::alt;
 use nom::bytes::complete::tag_no_case;
 use nom::character::complete::multispace0;
 use nom::combinator::map;
-use nom::combinator::opt;
-use nom::sequence::tuple;
-use nom::IResult;
+use nom::sequence::preceded;
+use nom::{Finish, IResult};
 
 #[derive(Debug, PartialEq)]
 pub struct OrderBy {
@@ -39,22 +39,48 @@ pub fn order_by_direction(i: &str) -> IResult<&str, OrderByDirection> {
     )(i)
 }
 
-pub fn parse_order_by(i: &str) -> IResult<&str, OrderBy> {
-    map(
-        tuple((identifier, multispace0, opt(order_by_direction))),
-        |tup| OrderBy {
-            identifier: tup.0,
-            ascending: match tup.2 {
-                Some(d) => d,
-                None => OrderByDirection::Ascending,
-            },
+/// Parses a whole order_by clause, surrounding whitespace included. If it doesn't parse, the
+/// error points at the first token that doesn't fit the grammar.
+pub fn parse_order_by_complete(input: &str) -> Result<OrderBy, ParseError> {
+    order_by_clause(input.trim_start())
+        .finish()
+        .map(|(_, ob)| ob)
+        .map_err(|e| e.into_parse_error(input))
+}
+
+fn order_by_clause(i: &str) -> SyntaxResult<'_, OrderBy> {
+    let (after_key, identifier) =
+        identifier(i).map_err(|_| SyntaxError::failure(i, "identifier"))?;
+    let key = &i[..i.len() - after_key.len()];
+    let (rest, direction) = match preceded(multispace0, order_by_direction)(after_key) {
+        Ok((rest, direction)) => (rest, Some(direction)),
+        Err(_) => (after_key, None),
+    };
+    if !rest.trim().is_empty() {
+        return Err(match direction {
+            Some(direction) => {
+                SyntaxError::failure(rest, format!("end of input after `{} {}`", key, direction))
+            }
+            None => SyntaxError::failure(
+                rest,
+                format!("ASC, DESC or end of input after identifier `{}`", key),
+            ),
+        });
+    }
+    Ok((
+        rest,
+        OrderBy {
+            identifier,
+            ascending: direction.unwrap_or(OrderByDirection::Ascending),
         },
-    )(i)
+    ))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{order_by_direction, parse_order_by, OrderBy, OrderByDirection};
+    use super::{
+        order_by_direction, parse_order_by_complete, OrderBy, OrderByDirection, ParseError,
+    };
     use crate::parser::common::{Entity, Identifier};
 
     #[test]
@@ -68,79 +94,107 @@ mod tests {
 
     #[test]
     fn test_parse_order_by() {
-        let res = parse_order_by("attr ASC").unwrap();
+        let res = parse_order_by_complete("attr ASC").unwrap();
         assert_eq!(
             res,
-            (
-                "",
-                OrderBy {
-                    identifier: Identifier {
-                        entity: Entity::Attribute,
-                        key: "attr".to_string()
-                    },
-                    ascending: OrderByDirection::Ascending
-                }
-            )
+            OrderBy {
+                identifier: Identifier {
+                    entity: Entity::Attribute,
+                    key: "attr".to_string()
+                },
+                ascending: OrderByDirection::Ascending
+            }
         );
 
-        let res = parse_order_by("attr DESC").unwrap();
+        let res = parse_order_by_complete("attr DESC").unwrap();
         assert_eq!(
             res,
-            (
-                "",
-                OrderBy {
-                    identifier: Identifier {
-                        entity: Entity::Attribute,
-                        key: "attr".to_string()
-                    },
-                    ascending: OrderByDirection::Descending
-                }
-            )
+            OrderBy {
+                identifier: Identifier {
+                    entity: Entity::Attribute,
+                    key: "attr".to_string()
+                },
+                ascending: OrderByDirection::Descending
+            }
         );
 
-        let res = parse_order_by("attr").unwrap();
+        let res = parse_order_by_complete("attr").unwrap();
         assert_eq!(
             res,
-            (
-                "",
-                OrderBy {
-                    identifier: Identifier {
-                        entity: Entity::Attribute,
-                        key: "attr".to_string()
-                    },
-                    ascending: OrderByDirection::Ascending
-                }
-            )
+            OrderBy {
+                identifier: Identifier {
+                    entity: Entity::Attribute,
+                    key: "attr".to_string()
+                },
+                ascending: OrderByDirection::Ascending
+            }
         );
 
-        let res = parse_order_by("attribute.attr").unwrap();
+        let res = parse_order_by_complete("attribute.attr").unwrap();
         assert_eq!(
             res,
-            (
-                "",
-                OrderBy {
-                    identifier: Identifier {
-                        entity: Entity::Attribute,
-                        key: "attr".to_string()
-                    },
-                    ascending: OrderByDirection::Ascending
-                }
-            )
+            OrderBy {
+                identifier: Identifier {
+                    entity: Entity::Attribute,
+                    key: "attr".to_string()
+                },
+                ascending: OrderByDirection::Ascending
+            }
         );
 
-        let res = parse_order_by("attr   ASC").unwrap();
+        let res = parse_order_by_complete("attr   ASC").unwrap();
         assert_eq!(
             res,
-            (
-                "",
-                OrderBy {
-                    identifier: Identifier {
-                        entity: Entity::Attribute,
-                        key: "attr".to_string()
-                    },
-                    ascending: OrderByDirection::Ascending
-                }
-            )
+            OrderBy {
+                identifier: Identifier {
+                    entity: Entity::Attribute,
+                    key: "attr".to_string()
+                },
+                ascending: OrderByDirection::Ascending
+            }
         );
     }
+
+    #[test]
+    fn test_parse_order_by_complete() {
+        assert_eq!(
+            parse_order_by_complete(" attr DESC ").unwrap(),
+            OrderBy {
+                identifier: Identifier {
+                    entity: Entity::Attribute,
+                    key: "attr".to_string()
+                },
+                ascending: OrderByDirection::Descending
+            }
+        );
+
+        for (order_by, offset, token, expected) in [
+            ("", 0, None, "identifier"),
+            (
+                "name; DROP TABLE experiments",
+                4,
+                Some(";"),
+                "ASC, DESC or end of input after identifier `name`",
+            ),
+            (
+                "metrics.`m` DESC LIMIT 1",
+                17,
+                Some("LIMIT"),
+                "end of input after `metrics.`m` DESC`",
+            ),
+        ] {
+            let e = parse_order_by_complete(order_by).unwrap_err();
+            assert_eq!(
+                e,
+                ParseError {
+                    offset,
+                    column: offset + 1,
+                    token: token.map(|t| t.to_string()),
+                    expected: expected.to_string(),
+                },
+                "order_by: {}",
+                order_by
+            );
+        }
+    }
 }
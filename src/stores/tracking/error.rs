@@ -1,34 +1,89 @@
 use actix_web::{http::header::ContentType, http::StatusCode, HttpResponse, ResponseError};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub enum MlflowError {
     DatabaseError(sqlx::Error),
     InvalidParameter(String),
+    ResourceDoesNotExist(String),
+    ResourceAlreadyExists(String),
+    InvalidState(String),
+    BadRequest(String),
+    InternalError(String),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ErrorResponse {
+    pub error_code: String,
     pub message: String,
 }
 
+// Extended result codes of SQLITE_CONSTRAINT_UNIQUE and SQLITE_CONSTRAINT_PRIMARYKEY, and the
+// SQLSTATE of unique_violation in Postgres.
+const UNIQUE_VIOLATION_CODES: [&str; 3] = ["2067", "1555", "23505"];
+
 impl From<sqlx::Error> for MlflowError {
     fn from(e: sqlx::Error) -> Self {
-        Self::DatabaseError(e)
+        match e {
+            sqlx::Error::RowNotFound => {
+                Self::ResourceDoesNotExist("The requested resource does not exist".to_string())
+            }
+            sqlx::Error::Database(ref db_err)
+                if db_err.code().map_or(false, |code| {
+                    UNIQUE_VIOLATION_CODES.contains(&code.as_ref())
+                }) =>
+            {
+                Self::ResourceAlreadyExists(db_err.message().to_string())
+            }
+            _ => Self::DatabaseError(e),
+        }
+    }
+}
+
+impl From<sqlx::migrate::MigrateError> for MlflowError {
+    fn from(e: sqlx::migrate::MigrateError) -> Self {
+        Self::InternalError(format!("Failed to migrate the database: {}", e))
+    }
+}
+
+impl MlflowError {
+    /// The MLflow error code returned to clients in `ErrorResponse::error_code`.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::DatabaseError(_) | Self::InternalError(_) => "INTERNAL_ERROR",
+            Self::InvalidParameter(_) => "INVALID_PARAMETER_VALUE",
+            Self::ResourceDoesNotExist(_) => "RESOURCE_DOES_NOT_EXIST",
+            Self::ResourceAlreadyExists(_) => "RESOURCE_ALREADY_EXISTS",
+            Self::InvalidState(_) => "INVALID_STATE",
+            Self::BadRequest(_) => "BAD_REQUEST",
+        }
     }
 }
 
 impl std::fmt::Display for MlflowError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Self::DatabaseError(e) => write!(f, "{}", e),
+            Self::InvalidParameter(message)
+            | Self::ResourceDoesNotExist(message)
+            | Self::ResourceAlreadyExists(message)
+            | Self::InvalidState(message)
+            | Self::BadRequest(message)
+            | Self::InternalError(message) => write!(f, "{}", message),
+        }
     }
 }
 
 impl ResponseError for MlflowError {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match *self {
-            Self::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            Self::InvalidParameter(_) => StatusCode::BAD_REQUEST,
+            Self::DatabaseError(_) | Self::InternalError(_) | Self::InvalidState(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            Self::InvalidParameter(_) | Self::ResourceAlreadyExists(_) | Self::BadRequest(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::ResourceDoesNotExist(_) => StatusCode::NOT_FOUND,
         }
     }
 
@@ -36,6 +91,7 @@ impl ResponseError for MlflowError {
         HttpResponse::build(self.status_code())
             .insert_header(ContentType::json())
             .json(ErrorResponse {
+                error_code: self.error_code().to_string(),
                 message: self.to_string(),
             })
     }
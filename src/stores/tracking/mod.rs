@@ -1,29 +1,102 @@
 pub mod error;
 pub mod postgres;
+mod query;
 pub mod sqlite;
 
-use crate::config::ServerConfig;
-use crate::entities::{Experiment, ExperimentTag, Run};
-use crate::parser::order_by;
+use crate::config::{PoolConfig, ServerConfig};
+use crate::entities::{
+    Experiment, ExperimentTag, Metric, MetricWithRunId, Param, Run, RunInfo, RunStatus, RunTag,
+    ViewType,
+};
+use crate::parser::common::{Entity, Identifier, Literal};
+use crate::parser::filter::{parse_filter_complete, Comparator, Comparison, Expression};
+use crate::parser::order_by::{parse_order_by_complete, OrderBy, OrderByDirection};
 use async_trait::async_trait;
 use error::MlflowError;
 use postgres::PostgresStore;
+use serde::{Deserialize, Serialize};
 use sqlite::SqliteStore;
+use std::collections::HashMap;
+use std::sync::Arc;
 use url::Url;
 
 #[async_trait]
-pub trait Store {
+pub trait Store: Send + Sync {
     async fn teardown(&self);
-    async fn search_runs(&self, experiment_ids: Vec<&str>) -> Result<Vec<Run>, MlflowError>;
+    async fn search_runs(
+        &self,
+        experiment_ids: Vec<&str>,
+        filter: Option<&str>,
+        run_view_type: ViewType,
+        order_by: Option<Vec<&str>>,
+        max_results: Option<i64>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Run>, MlflowError>;
     async fn get_run(&self, run_id: &str) -> Result<Run, MlflowError>;
-    async fn list_experiments(&self) -> Result<Vec<Experiment>, MlflowError>;
+    async fn create_run(
+        &self,
+        experiment_id: &str,
+        user_id: Option<&str>,
+        start_time: Option<i64>,
+        run_name: Option<&str>,
+        tags: Option<Vec<&RunTag>>,
+    ) -> Result<Run, MlflowError>;
+    async fn update_run(
+        &self,
+        run_id: &str,
+        status: Option<RunStatus>,
+        end_time: Option<i64>,
+        run_name: Option<&str>,
+    ) -> Result<RunInfo, MlflowError>;
+    async fn delete_run(&self, run_id: &str) -> Result<Run, MlflowError>;
+    async fn restore_run(&self, run_id: &str) -> Result<Run, MlflowError>;
+    async fn log_metric(&self, run_id: &str, metric: &Metric) -> Result<(), MlflowError>;
+    async fn log_param(&self, run_id: &str, param: &Param) -> Result<(), MlflowError>;
+    async fn set_tag(&self, run_id: &str, tag: &RunTag) -> Result<(), MlflowError>;
+    async fn delete_tag(&self, run_id: &str, key: &str) -> Result<(), MlflowError>;
+    async fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<&Metric>,
+        params: Vec<&Param>,
+        tags: Vec<&RunTag>,
+    ) -> Result<(), MlflowError>;
+    async fn get_metric_history(
+        &self,
+        run_id: &str,
+        metric_key: &str,
+        max_results: Option<i64>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Metric>, MlflowError>;
+    /// Returns the history of a metric for each of the given runs, sampled down to at most
+    /// `max_results` evenly spaced steps per run. The metrics are grouped by run, in the order of
+    /// `run_ids`, and sorted by step and timestamp within each run.
+    async fn get_metric_history_bulk_interval(
+        &self,
+        run_ids: Vec<&str>,
+        metric_key: &str,
+        start_step: Option<i64>,
+        end_step: Option<i64>,
+        max_results: Option<i64>,
+    ) -> Result<Vec<MetricWithRunId>, MlflowError>;
+    async fn list_experiments(
+        &self,
+        max_results: Option<i64>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Experiment>, MlflowError>;
     async fn search_experiments(
         &self,
         max_results: Option<i64>,
         filter_string: Option<&str>,
+        view_type: ViewType,
         order_by: Option<Vec<&str>>,
-    ) -> Result<Vec<Experiment>, MlflowError>;
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Experiment>, MlflowError>;
     async fn get_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError>;
+    async fn get_experiment_by_name(
+        &self,
+        experiment_name: &str,
+    ) -> Result<Experiment, MlflowError>;
     async fn create_experiment(
         &self,
         name: &str,
@@ -37,30 +110,531 @@ pub trait Store {
         experiment_id: &str,
         new_name: &str,
     ) -> Result<Experiment, MlflowError>;
+    async fn set_experiment_tag(
+        &self,
+        experiment_id: &str,
+        tag: &ExperimentTag,
+    ) -> Result<(), MlflowError>;
+    async fn delete_experiment_tag(
+        &self,
+        experiment_id: &str,
+        key: &str,
+    ) -> Result<(), MlflowError>;
+}
+
+pub const RUN_NAME_TAG: &str = "mlflow.runName";
+
+/// The MLflow schema revision, as recorded in `alembic_version`, that the stores are written
+/// against and that the migrations in `migrations/` create.
+pub const SCHEMA_VERSION: &str = "cc1f77228345";
+
+/// Refuses a database whose schema was not created at `SCHEMA_VERSION`, since the stores would
+/// fail on it in unpredictable ways later on.
+fn check_schema_version(version: Option<String>) -> Result<(), MlflowError> {
+    match version {
+        Some(version) if version == SCHEMA_VERSION => Ok(()),
+        Some(version) => Err(MlflowError::InvalidState(format!(
+            "Unsupported database schema version {}, expected {}",
+            version, SCHEMA_VERSION
+        ))),
+        None => Err(MlflowError::InvalidState(
+            "The database is not empty and has no MLflow schema version".to_string(),
+        )),
+    }
+}
+
+pub const SEARCH_MAX_RESULTS_DEFAULT: i64 = 1000;
+pub const SEARCH_MAX_RESULTS_THRESHOLD: i64 = 50000;
+
+/// A page of search results and the token to fetch the next page with, if there is one.
+pub struct PagedList<T> {
+    pub items: Vec<T>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PageToken {
+    offset: i64,
+}
+
+fn encode_page_token(offset: i64) -> String {
+    base64::encode(serde_json::to_string(&PageToken { offset }).unwrap())
+}
+
+/// Decodes a page token into the offset of the page. A missing or empty token is the first page.
+fn decode_page_token(page_token: Option<&str>) -> Result<i64, MlflowError> {
+    let page_token = match page_token {
+        Some(t) if !t.is_empty() => t,
+        _ => return Ok(0),
+    };
+    let invalid_token =
+        || MlflowError::InvalidParameter(format!("Invalid page token: {}", page_token));
+    let decoded = base64::decode(page_token).map_err(|_| invalid_token())?;
+    let token: PageToken = serde_json::from_slice(&decoded).map_err(|_| invalid_token())?;
+    if token.offset < 0 {
+        return Err(invalid_token());
+    }
+    Ok(token.offset)
+}
+
+fn check_max_results(max_results: i64) -> Result<i64, MlflowError> {
+    if max_results <= 0 || max_results > SEARCH_MAX_RESULTS_THRESHOLD {
+        return Err(MlflowError::InvalidParameter(format!(
+            "Invalid value {} for parameter 'max_results' supplied. It must be a positive \
+             integer at most {}",
+            max_results, SEARCH_MAX_RESULTS_THRESHOLD
+        )));
+    }
+    Ok(max_results)
+}
+
+/// Checks the page size of a search, defaulting to `SEARCH_MAX_RESULTS_DEFAULT`.
+fn validate_max_results(max_results: Option<i64>) -> Result<i64, MlflowError> {
+    check_max_results(max_results.unwrap_or(SEARCH_MAX_RESULTS_DEFAULT))
+}
+
+/// Builds a page from the rows fetched with `LIMIT max_results + 1 OFFSET offset`. The extra
+/// row only tells whether there is a next page.
+fn paginate<T>(mut items: Vec<T>, offset: i64, max_results: Option<i64>) -> PagedList<T> {
+    let next_page_token = match max_results {
+        Some(m) if items.len() as i64 > m => {
+            items.truncate(m as usize);
+            Some(encode_page_token(offset + m))
+        }
+        _ => None,
+    };
+    PagedList {
+        items,
+        next_page_token,
+    }
+}
+
+/// Maps a run attribute that can be used in a search filter to its column in `runs`.
+fn run_attribute_column(key: &str) -> Result<&'static str, MlflowError> {
+    match key {
+        "run_id" | "run_uuid" => Ok("run_uuid"),
+        "run_name" => Ok("name"),
+        "status" => Ok("status"),
+        "user_id" => Ok("user_id"),
+        "artifact_uri" => Ok("artifact_uri"),
+        "start_time" => Ok("start_time"),
+        "end_time" => Ok("end_time"),
+        _ => Err(MlflowError::InvalidParameter(format!(
+            "Invalid attribute key '{}' specified. Valid keys are run_id, run_name, status, \
+             user_id, artifact_uri, start_time and end_time",
+            key
+        ))),
+    }
+}
+
+/// Maps an experiment attribute that can be used in a search to its column in `experiments`.
+fn experiment_attribute_column(key: &str) -> Result<&'static str, MlflowError> {
+    match key {
+        "experiment_id" => Ok("experiment_id"),
+        "name" => Ok("name"),
+        "artifact_location" => Ok("artifact_location"),
+        "lifecycle_stage" => Ok("lifecycle_stage"),
+        _ => Err(MlflowError::InvalidParameter(format!(
+            "Invalid attribute key '{}' specified. Valid keys are experiment_id, name, \
+             artifact_location and lifecycle_stage",
+            key
+        ))),
+    }
+}
+
+fn validate_comparison(c: &Comparison, is_numeric: bool) -> Result<(), MlflowError> {
+    let is_valid_value = |l: &Literal| {
+        if is_numeric {
+            matches!(l, Literal::Integer(_) | Literal::Float(_))
+        } else {
+            matches!(l, Literal::String(_))
+        }
+    };
+    let is_valid = match (&c.operator, &c.right) {
+        (Comparator::In | Comparator::NotIn, Literal::List(literals)) => {
+            literals.iter().all(is_valid_value)
+        }
+        (Comparator::IsNull | Comparator::IsNotNull, right) => *right == Literal::Null,
+        (Comparator::In | Comparator::NotIn, _) | (_, Literal::List(_) | Literal::Null) => false,
+        (Comparator::Equal | Comparator::NotEqual, right) => is_valid_value(right),
+        (Comparator::Like | Comparator::ILike, right) => !is_numeric && is_valid_value(right),
+        (_, right) => is_numeric && is_valid_value(right),
+    };
+    if !is_valid {
+        return Err(MlflowError::InvalidParameter(format!(
+            "Invalid comparison '{} {} {}': {} {}",
+            c.left.key,
+            c.operator,
+            c.right,
+            c.left.key,
+            if is_numeric {
+                "must be compared to a number with =, !=, <, <=, >, >=, IN or NOT IN"
+            } else {
+                "must be compared to a string with =, !=, LIKE, ILIKE, IN or NOT IN"
+            }
+        )));
+    }
+    Ok(())
+}
+
+fn parse_filter_string(filter: Option<&str>) -> Result<Option<Expression>, MlflowError> {
+    parse_filter_complete(filter.unwrap_or(""))
+        .map_err(|e| MlflowError::InvalidParameter(format!("Invalid filter: {}", e)))
+}
+
+fn parse_order_by_clauses(order_by: Option<Vec<&str>>) -> Result<Vec<OrderBy>, MlflowError> {
+    order_by
+        .unwrap_or_default()
+        .into_iter()
+        .map(|ob| {
+            parse_order_by_complete(ob).map_err(|e| {
+                MlflowError::InvalidParameter(format!("Invalid order by clause: {}", e))
+            })
+        })
+        .collect()
+}
+
+fn parse_run_filter(filter: Option<&str>) -> Result<Option<Expression>, MlflowError> {
+    let expression = parse_filter_string(filter)?;
+    for c in expression.iter().flat_map(|e| e.comparisons()) {
+        let is_numeric = match c.left.entity {
+            Entity::Attribute => matches!(
+                run_attribute_column(&c.left.key)?,
+                "start_time" | "end_time"
+            ),
+            Entity::Metric => true,
+            Entity::Param | Entity::Tag => false,
+        };
+        validate_comparison(c, is_numeric)?;
+    }
+    Ok(expression)
+}
+
+fn parse_run_order_by(order_by: Option<Vec<&str>>) -> Result<Vec<OrderBy>, MlflowError> {
+    let order_by_clauses = parse_order_by_clauses(order_by)?;
+    for ob in order_by_clauses.iter() {
+        if ob.identifier.entity == Entity::Attribute {
+            run_attribute_column(&ob.identifier.key)?;
+        }
+    }
+    Ok(order_by_clauses)
+}
+
+fn parse_experiment_filter(filter: Option<&str>) -> Result<Option<Expression>, MlflowError> {
+    let expression = parse_filter_string(filter)?;
+    for c in expression.iter().flat_map(|e| e.comparisons()) {
+        let is_numeric = match c.left.entity {
+            Entity::Attribute => experiment_attribute_column(&c.left.key)? == "experiment_id",
+            Entity::Tag => false,
+            Entity::Metric | Entity::Param => {
+                return Err(MlflowError::InvalidParameter(
+                    "Experiment search only supports filtering by attribute or tag".to_string(),
+                ))
+            }
+        };
+        validate_comparison(c, is_numeric)?;
+    }
+    Ok(expression)
+}
+
+/// Parses the order_by clauses of an experiment search, adding `experiment_id` as a
+/// tie-breaker if it's not present.
+fn parse_experiment_order_by(order_by: Option<Vec<&str>>) -> Result<Vec<OrderBy>, MlflowError> {
+    let mut order_by_clauses = parse_order_by_clauses(order_by)?;
+    for ob in order_by_clauses.iter() {
+        if ob.identifier.entity != Entity::Attribute {
+            return Err(MlflowError::InvalidParameter(
+                "Experiment search only supports ordering by attribute".to_string(),
+            ));
+        }
+        experiment_attribute_column(&ob.identifier.key)?;
+    }
+    if !order_by_clauses
+        .iter()
+        .any(|ob| ob.identifier.key == "experiment_id")
+    {
+        order_by_clauses.push(OrderBy {
+            identifier: Identifier {
+                entity: Entity::Attribute,
+                key: "experiment_id".to_string(),
+            },
+            ascending: OrderByDirection::Ascending,
+        });
+    }
+    Ok(order_by_clauses)
+}
+
+fn run_not_found(run_id: &str) -> MlflowError {
+    MlflowError::ResourceDoesNotExist(format!("Run with id={} not found", run_id))
+}
+
+fn experiment_not_found(experiment_id: impl std::fmt::Display) -> MlflowError {
+    MlflowError::ResourceDoesNotExist(format!("No Experiment with id={} exists", experiment_id))
+}
+
+/// Maps a unique violation on `experiments.name` to an error naming the experiment, rather than
+/// passing the database's message through.
+fn experiment_name_conflict(name: &str) -> impl FnOnce(sqlx::Error) -> MlflowError + '_ {
+    move |e| match MlflowError::from(e) {
+        MlflowError::ResourceAlreadyExists(_) => {
+            MlflowError::ResourceAlreadyExists(format!("Experiment(name={}) already exists", name))
+        }
+        e => e,
+    }
+}
+
+fn parse_experiment_id(experiment_id: &str) -> Result<i32, MlflowError> {
+    experiment_id.parse::<i32>().map_err(|_| {
+        MlflowError::InvalidParameter(format!("Invalid experiment id: {}", experiment_id))
+    })
+}
+
+fn parse_experiment_ids(experiment_ids: Vec<&str>) -> Result<Vec<i32>, MlflowError> {
+    experiment_ids
+        .into_iter()
+        .map(parse_experiment_id)
+        .collect()
+}
+
+pub const MAX_RUN_IDS_PER_BULK_INTERVAL_REQUEST: usize = 500;
+pub const BULK_INTERVAL_MAX_RESULTS_DEFAULT: i64 = 320;
+pub const BULK_INTERVAL_MAX_RESULTS_THRESHOLD: i64 = 1000;
+
+/// Validates a bulk metric history request and returns the number of steps to sample per run.
+fn validate_bulk_interval_request(
+    run_ids: &[&str],
+    start_step: Option<i64>,
+    end_step: Option<i64>,
+    max_results: Option<i64>,
+) -> Result<i64, MlflowError> {
+    if run_ids.is_empty() || run_ids.len() > MAX_RUN_IDS_PER_BULK_INTERVAL_REQUEST {
+        return Err(MlflowError::InvalidParameter(format!(
+            "Between 1 and {} run ids must be specified. Got {}.",
+            MAX_RUN_IDS_PER_BULK_INTERVAL_REQUEST,
+            run_ids.len()
+        )));
+    }
+    if let (Some(start_step), Some(end_step)) = (start_step, end_step) {
+        if start_step > end_step {
+            return Err(MlflowError::InvalidParameter(format!(
+                "end_step ({}) must be greater than or equal to start_step ({})",
+                end_step, start_step
+            )));
+        }
+    }
+    match max_results {
+        Some(m) if m <= 0 || m > BULK_INTERVAL_MAX_RESULTS_THRESHOLD => {
+            Err(MlflowError::InvalidParameter(format!(
+                "Invalid value {} for parameter 'max_results' supplied. It must be a positive \
+                 integer at most {}",
+                m, BULK_INTERVAL_MAX_RESULTS_THRESHOLD
+            )))
+        }
+        Some(m) => Ok(m),
+        None => Ok(BULK_INTERVAL_MAX_RESULTS_DEFAULT),
+    }
+}
+
+/// Sorts sampled metrics by the position of their run in the request, keeping the SQL order
+/// (step, timestamp) within each run.
+fn group_by_run(run_ids: &[&str], mut metrics: Vec<MetricWithRunId>) -> Vec<MetricWithRunId> {
+    let positions: HashMap<&str, usize> = run_ids
+        .iter()
+        .enumerate()
+        .map(|(i, run_id)| (*run_id, i))
+        .rev()
+        .collect();
+    metrics.sort_by_key(|m| positions.get(m.run_id.as_str()).copied());
+    metrics
+}
+
+pub const MAX_METRICS_PER_BATCH: usize = 1000;
+pub const MAX_PARAMS_PER_BATCH: usize = 100;
+pub const MAX_TAGS_PER_BATCH: usize = 100;
+pub const MAX_ENTITIES_PER_BATCH: usize = 1000;
+
+fn validate_batch_log_limits(
+    metrics: &[&Metric],
+    params: &[&Param],
+    tags: &[&RunTag],
+) -> Result<(), MlflowError> {
+    for (name, count, limit) in [
+        ("metrics", metrics.len(), MAX_METRICS_PER_BATCH),
+        ("params", params.len(), MAX_PARAMS_PER_BATCH),
+        ("tags", tags.len(), MAX_TAGS_PER_BATCH),
+        (
+            "total entities",
+            metrics.len() + params.len() + tags.len(),
+            MAX_ENTITIES_PER_BATCH,
+        ),
+    ] {
+        if count > limit {
+            return Err(MlflowError::InvalidParameter(format!(
+                "A batch logging request can contain at most {} {}. Got {} {}. \
+                 Please split up {} across multiple requests and try again.",
+                limit, name, count, name, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+struct PreparedBatch<'a> {
+    /// Params that aren't logged yet.
+    params: Vec<&'a Param>,
+    /// Tags to upsert. The last value wins for duplicate keys.
+    tags: Vec<&'a RunTag>,
+    /// The latest metric for each key.
+    latest_metrics: Vec<&'a Metric>,
+}
+
+/// Validates a log-batch request against the batch limits and the params already logged.
+fn prepare_batch<'a>(
+    run_id: &str,
+    metrics: &[&'a Metric],
+    params: Vec<&'a Param>,
+    tags: Vec<&'a RunTag>,
+    existing_params: Vec<(String, String)>,
+) -> Result<PreparedBatch<'a>, MlflowError> {
+    validate_batch_log_limits(metrics, &params, &tags)?;
+
+    let mut logged_params: HashMap<String, String> = existing_params.into_iter().collect();
+    let mut new_params: Vec<&Param> = vec![];
+    for param in params {
+        match logged_params.get(&param.key) {
+            Some(value) if value != &param.value => {
+                return Err(MlflowError::InvalidParameter(format!(
+                    "Changing param values is not allowed. Param with key='{}' was already \
+                     logged with value='{}' for run ID='{}'. Attempted logging new value '{}'.",
+                    param.key, value, run_id, param.value
+                )));
+            }
+            Some(_) => {}
+            None => {
+                logged_params.insert(param.key.clone(), param.value.clone());
+                new_params.push(param);
+            }
+        }
+    }
+
+    let mut tags_by_key: HashMap<&str, &RunTag> = HashMap::new();
+    for tag in tags {
+        tags_by_key.insert(tag.key.as_str(), tag);
+    }
+
+    let mut latest_metrics: HashMap<&str, &Metric> = HashMap::new();
+    for metric in metrics {
+        let is_newer = match latest_metrics.get(metric.key.as_str()) {
+            Some(latest) => {
+                (metric.step, metric.timestamp, metric.value)
+                    >= (latest.step, latest.timestamp, latest.value)
+            }
+            None => true,
+        };
+        if is_newer {
+            latest_metrics.insert(metric.key.as_str(), metric);
+        }
+    }
+
+    Ok(PreparedBatch {
+        params: new_params,
+        tags: tags_by_key.into_values().collect(),
+        latest_metrics: latest_metrics.into_values().collect(),
+    })
 }
 
 pub async fn get_store(
     uri: &str,
     default_artifact_root: &str,
-) -> Result<Box<dyn Store>, MlflowError> {
-    let parsed = Url::parse(uri).unwrap();
+    pool_config: &PoolConfig,
+) -> Result<Arc<dyn Store>, MlflowError> {
+    let parsed = Url::parse(uri).map_err(|e| {
+        MlflowError::InvalidParameter(format!("Invalid backend store URI '{}': {}", uri, e))
+    })?;
     match parsed.scheme() {
-        "postgresql" => Ok(Box::new(
-            PostgresStore::new(uri, default_artifact_root).await?,
+        "postgresql" => Ok(Arc::new(
+            PostgresStore::new(uri, default_artifact_root, pool_config).await?,
         )),
-        "sqlite" => Ok(Box::new(
-            SqliteStore::new(uri, default_artifact_root).await?,
+        "sqlite" => Ok(Arc::new(
+            SqliteStore::new(uri, default_artifact_root, pool_config).await?,
         )),
-        _ => panic!("Unsupported URI: {}", uri),
+        scheme => Err(MlflowError::InvalidParameter(format!(
+            "Unsupported backend store URI scheme '{}'. Supported schemes are postgresql and sqlite",
+            scheme
+        ))),
     }
 }
 
+/// Builds the store shared by all requests. Call once at startup.
 pub async fn get_store_from_server_config(
     server_config: &ServerConfig,
-) -> Result<Box<dyn Store>, MlflowError> {
+) -> Result<Arc<dyn Store>, MlflowError> {
     get_store(
         server_config.backend_store_uri.as_str(),
         server_config.default_artifact_root.as_str(),
+        &server_config.pool,
     )
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_schema_version, decode_page_token, encode_page_token, get_store,
+        validate_max_results, SCHEMA_VERSION, SEARCH_MAX_RESULTS_DEFAULT,
+        SEARCH_MAX_RESULTS_THRESHOLD,
+    };
+    use crate::config::PoolConfig;
+    use crate::stores::tracking::error::MlflowError;
+
+    #[tokio::test]
+    async fn test_get_store_invalid_uri() {
+        for uri in ["not a uri", "mysql://localhost/mlflow"] {
+            let res = get_store(uri, "./artifacts", &PoolConfig::default()).await;
+            assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        }
+    }
+
+    #[test]
+    fn test_check_schema_version() {
+        assert!(check_schema_version(Some(SCHEMA_VERSION.to_string())).is_ok());
+        match check_schema_version(Some("97727af70f4d".to_string())) {
+            Err(MlflowError::InvalidState(message)) => assert_eq!(
+                message,
+                format!(
+                    "Unsupported database schema version 97727af70f4d, expected {}",
+                    SCHEMA_VERSION
+                )
+            ),
+            _ => panic!("expected an invalid state error"),
+        }
+        assert!(matches!(
+            check_schema_version(None),
+            Err(MlflowError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn test_page_token() {
+        assert_eq!(decode_page_token(None).unwrap(), 0);
+        assert_eq!(decode_page_token(Some("")).unwrap(), 0);
+        let token = encode_page_token(42);
+        assert_eq!(decode_page_token(Some(token.as_str())).unwrap(), 42);
+        assert!(decode_page_token(Some("abc")).is_err());
+    }
+
+    #[test]
+    fn test_validate_max_results() {
+        assert_eq!(
+            validate_max_results(None).unwrap(),
+            SEARCH_MAX_RESULTS_DEFAULT
+        );
+        assert_eq!(validate_max_results(Some(1)).unwrap(), 1);
+        for max_results in [0, -1, SEARCH_MAX_RESULTS_THRESHOLD + 1] {
+            assert!(matches!(
+                validate_max_results(Some(max_results)),
+                Err(MlflowError::InvalidParameter(_))
+            ));
+        }
+    }
+}
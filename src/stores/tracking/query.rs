@@ -0,0 +1,441 @@
+//! Compiles parsed filters and order_by clauses into SQL with bound parameters. Only column
+//! names from the attribute whitelists and fixed table names are written into the SQL; keys and
+//! values are always bound.
+use crate::parser::common::{Entity, Literal};
+use crate::parser::filter::{Comparator, Comparison, Expression};
+use crate::parser::order_by::OrderBy;
+use crate::stores::tracking::error::MlflowError;
+use crate::stores::tracking::{experiment_attribute_column, run_attribute_column};
+use sqlx::{Database, Postgres, QueryBuilder, Sqlite};
+
+/// The differences between the databases that matter when compiling a query.
+pub trait Dialect: Database {
+    /// Whether `ILIKE` is supported. Otherwise it's emulated with `LOWER(..) LIKE LOWER(..)`.
+    const HAS_ILIKE: bool;
+
+    fn push_bind_str<'a>(query_builder: &mut QueryBuilder<'a, Self>, value: &'a str);
+    fn push_bind_i64(query_builder: &mut QueryBuilder<'_, Self>, value: i64);
+    fn push_bind_f64(query_builder: &mut QueryBuilder<'_, Self>, value: f64);
+}
+
+impl Dialect for Sqlite {
+    const HAS_ILIKE: bool = false;
+
+    fn push_bind_str<'a>(query_builder: &mut QueryBuilder<'a, Self>, value: &'a str) {
+        query_builder.push_bind(value);
+    }
+
+    fn push_bind_i64(query_builder: &mut QueryBuilder<'_, Self>, value: i64) {
+        query_builder.push_bind(value);
+    }
+
+    fn push_bind_f64(query_builder: &mut QueryBuilder<'_, Self>, value: f64) {
+        query_builder.push_bind(value);
+    }
+}
+
+impl Dialect for Postgres {
+    const HAS_ILIKE: bool = true;
+
+    fn push_bind_str<'a>(query_builder: &mut QueryBuilder<'a, Self>, value: &'a str) {
+        query_builder.push_bind(value);
+    }
+
+    // Prepared statements are cached by their SQL text, and a cached statement keeps the parameter
+    // types it was prepared with. The casts make the type of each number part of the text, so an
+    // integer is never sent to a statement that expects a DOUBLE PRECISION or the other way round.
+    fn push_bind_i64(query_builder: &mut QueryBuilder<'_, Self>, value: i64) {
+        query_builder.push_bind(value).push("::BIGINT");
+    }
+
+    fn push_bind_f64(query_builder: &mut QueryBuilder<'_, Self>, value: f64) {
+        query_builder.push_bind(value).push("::DOUBLE PRECISION");
+    }
+}
+
+fn push_literal<'a, DB: Dialect>(query_builder: &mut QueryBuilder<'a, DB>, literal: &'a Literal) {
+    match literal {
+        Literal::String(s) => DB::push_bind_str(query_builder, s),
+        Literal::Integer(i) => DB::push_bind_i64(query_builder, *i),
+        Literal::Float(f) => DB::push_bind_f64(query_builder, *f),
+        Literal::Null => {
+            query_builder.push("NULL");
+        }
+        Literal::List(literals) => {
+            query_builder.push("(");
+            for (i, l) in literals.iter().enumerate() {
+                if i > 0 {
+                    query_builder.push(", ");
+                }
+                push_literal(query_builder, l);
+            }
+            query_builder.push(")");
+        }
+    }
+}
+
+/// Pushes `column <comparator> <value>`. `column` must not come from user input.
+fn push_comparison<'a, DB: Dialect>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    column: &str,
+    comparator: &Comparator,
+    value: &'a Literal,
+) {
+    match comparator {
+        Comparator::ILike if !DB::HAS_ILIKE => {
+            query_builder.push(format!("LOWER({}) LIKE LOWER(", column));
+            push_literal(query_builder, value);
+            query_builder.push(")");
+        }
+        _ => {
+            query_builder.push(format!("{} {} ", column, comparator));
+            push_literal(query_builder, value);
+        }
+    }
+}
+
+/// Pushes a filter expression, compiling each comparison with `push_comparison`. Every operand
+/// is parenthesized, so the precedence of the tree is kept.
+fn push_expression<'a, DB: Dialect, F>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    expression: &'a Expression,
+    push_comparison: F,
+) -> Result<(), MlflowError>
+where
+    F: Fn(&mut QueryBuilder<'a, DB>, &'a Comparison) -> Result<(), MlflowError> + Copy,
+{
+    match expression {
+        Expression::Comparison(c) => push_comparison(query_builder, c)?,
+        Expression::And(operands) | Expression::Or(operands) => {
+            let separator = if matches!(expression, Expression::And(..)) {
+                ") AND ("
+            } else {
+                ") OR ("
+            };
+            query_builder.push("(");
+            for (i, operand) in operands.iter().enumerate() {
+                if i > 0 {
+                    query_builder.push(separator);
+                }
+                push_expression(query_builder, operand, push_comparison)?;
+            }
+            query_builder.push(")");
+        }
+        Expression::Not(e) => {
+            query_builder.push("NOT (");
+            push_expression(query_builder, e, push_comparison)?;
+            query_builder.push(")");
+        }
+    }
+    Ok(())
+}
+
+/// Pushes a run search filter, parenthesized so that it can be `AND`ed with other conditions.
+pub fn push_run_filter<'a, DB: Dialect>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    expression: &'a Expression,
+) -> Result<(), MlflowError> {
+    query_builder.push("(");
+    push_expression(query_builder, expression, push_run_comparison)?;
+    query_builder.push(")");
+    Ok(())
+}
+
+/// Pushes an experiment search filter, parenthesized so that it can be `AND`ed with other
+/// conditions.
+pub fn push_experiment_filter<'a, DB: Dialect>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    expression: &'a Expression,
+) -> Result<(), MlflowError> {
+    query_builder.push("(");
+    push_expression(query_builder, expression, push_experiment_comparison)?;
+    query_builder.push(")");
+    Ok(())
+}
+
+fn is_null_check(comparator: &Comparator) -> bool {
+    matches!(comparator, Comparator::IsNull | Comparator::IsNotNull)
+}
+
+/// Pushes a comparison against a run attribute, or an `EXISTS` subquery against the latest
+/// metrics, params or tags of the run. `IS NULL` on a metric, param or tag becomes a
+/// `NOT EXISTS` subquery matching runs that never logged the key.
+fn push_run_comparison<'a, DB: Dialect>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    c: &'a Comparison,
+) -> Result<(), MlflowError> {
+    let table = match c.left.entity {
+        Entity::Attribute => {
+            let column = format!("runs.{}", run_attribute_column(&c.left.key)?);
+            push_comparison(query_builder, &column, &c.operator, &c.right);
+            return Ok(());
+        }
+        Entity::Metric => "latest_metrics",
+        Entity::Param => "params",
+        Entity::Tag => "tags",
+    };
+    if c.operator == Comparator::IsNull {
+        query_builder.push("NOT ");
+    }
+    query_builder.push(format!(
+        "EXISTS (SELECT 1 FROM {table} WHERE {table}.run_uuid = runs.run_uuid AND {table}.key = ",
+        table = table
+    ));
+    DB::push_bind_str(query_builder, &c.left.key);
+    if !is_null_check(&c.operator) {
+        query_builder.push(" AND ");
+        if c.left.entity == Entity::Metric {
+            query_builder.push("NOT latest_metrics.is_nan AND ");
+        }
+        push_comparison(
+            query_builder,
+            &format!("{}.value", table),
+            &c.operator,
+            &c.right,
+        );
+    }
+    query_builder.push(")");
+    Ok(())
+}
+
+/// Pushes a `LEFT JOIN` for each metric, param or tag the runs are ordered by, aliased as
+/// `order_<index>`.
+pub fn push_run_order_by_joins<'a, DB: Dialect>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    order_by: &'a [OrderBy],
+) {
+    for (i, ob) in order_by.iter().enumerate() {
+        let table = match ob.identifier.entity {
+            Entity::Attribute => continue,
+            Entity::Metric => "latest_metrics",
+            Entity::Param => "params",
+            Entity::Tag => "tags",
+        };
+        query_builder.push(format!(
+            " LEFT JOIN {table} AS order_{i} ON order_{i}.run_uuid = runs.run_uuid AND order_{i}.key = ",
+            table = table,
+            i = i
+        ));
+        DB::push_bind_str(query_builder, &ob.identifier.key);
+    }
+}
+
+/// Pushes the `ORDER BY` clause of a run search. Like MLflow, runs missing the sort key (and NaN
+/// metrics) are placed last regardless of the direction, and ties are broken by
+/// `start_time DESC, run_uuid`.
+pub fn push_run_order_by<DB: Dialect>(
+    query_builder: &mut QueryBuilder<'_, DB>,
+    order_by: &[OrderBy],
+) -> Result<(), MlflowError> {
+    query_builder.push(" ORDER BY ");
+    for (i, ob) in order_by.iter().enumerate() {
+        let (column, is_nan) = match ob.identifier.entity {
+            Entity::Attribute => (
+                format!("runs.{}", run_attribute_column(&ob.identifier.key)?),
+                "FALSE".to_string(),
+            ),
+            Entity::Metric => (format!("order_{}.value", i), format!("order_{}.is_nan", i)),
+            Entity::Param | Entity::Tag => (format!("order_{}.value", i), "FALSE".to_string()),
+        };
+        query_builder.push(format!(
+            "CASE WHEN {column} IS NULL THEN 2 WHEN {is_nan} THEN 1 ELSE 0 END, {column} {direction}, ",
+            column = column,
+            is_nan = is_nan,
+            direction = ob.ascending
+        ));
+    }
+    query_builder.push("runs.start_time DESC, runs.run_uuid");
+    Ok(())
+}
+
+/// Pushes a comparison against an experiment attribute, or an `EXISTS` subquery against the
+/// tags of the experiment.
+fn push_experiment_comparison<'a, DB: Dialect>(
+    query_builder: &mut QueryBuilder<'a, DB>,
+    c: &'a Comparison,
+) -> Result<(), MlflowError> {
+    match c.left.entity {
+        Entity::Attribute => {
+            let column = format!("experiments.{}", experiment_attribute_column(&c.left.key)?);
+            push_comparison(query_builder, &column, &c.operator, &c.right);
+        }
+        Entity::Tag => {
+            if c.operator == Comparator::IsNull {
+                query_builder.push("NOT ");
+            }
+            query_builder.push(
+                "EXISTS (SELECT 1 FROM experiment_tags \
+                 WHERE experiment_tags.experiment_id = experiments.experiment_id \
+                 AND experiment_tags.key = ",
+            );
+            DB::push_bind_str(query_builder, &c.left.key);
+            if !is_null_check(&c.operator) {
+                query_builder.push(" AND ");
+                push_comparison(
+                    query_builder,
+                    "experiment_tags.value",
+                    &c.operator,
+                    &c.right,
+                );
+            }
+            query_builder.push(")");
+        }
+        Entity::Metric | Entity::Param => {
+            return Err(MlflowError::InvalidParameter(
+                "Experiment search only supports filtering by attribute or tag".to_string(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Pushes the `ORDER BY` clause of an experiment search.
+pub fn push_experiment_order_by<DB: Dialect>(
+    query_builder: &mut QueryBuilder<'_, DB>,
+    order_by: &[OrderBy],
+) -> Result<(), MlflowError> {
+    query_builder.push(" ORDER BY ");
+    let mut separated = query_builder.separated(", ");
+    for ob in order_by {
+        if ob.identifier.entity != Entity::Attribute {
+            return Err(MlflowError::InvalidParameter(
+                "Experiment search only supports ordering by attribute".to_string(),
+            ));
+        }
+        separated.push(format!(
+            "experiments.{} {}",
+            experiment_attribute_column(&ob.identifier.key)?,
+            ob.ascending
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::filter::parse_filter;
+    use crate::parser::order_by::parse_order_by_complete;
+    use sqlx::Execute;
+
+    fn sqlite_experiment_filter(filter: &str) -> Result<String, MlflowError> {
+        let (_, expression) = parse_filter(filter).unwrap();
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("WHERE TRUE AND ");
+        push_experiment_filter(&mut query_builder, expression.as_ref().unwrap())?;
+        Ok(query_builder.build().sql().to_string())
+    }
+
+    fn postgres_experiment_filter(filter: &str) -> Result<String, MlflowError> {
+        let (_, expression) = parse_filter(filter).unwrap();
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("WHERE TRUE AND ");
+        push_experiment_filter(&mut query_builder, expression.as_ref().unwrap())?;
+        Ok(query_builder.build().sql().to_string())
+    }
+
+    #[test]
+    fn test_values_are_bound() {
+        let sql = sqlite_experiment_filter("name = 'x OR 1=1 --' AND tag.key LIKE '%a'").unwrap();
+        assert_eq!(
+            sql,
+            "WHERE TRUE AND ((experiments.name = ?) AND (EXISTS (SELECT 1 FROM experiment_tags \
+             WHERE experiment_tags.experiment_id = experiments.experiment_id \
+             AND experiment_tags.key = ? AND experiment_tags.value LIKE ?)))"
+        );
+        let sql = postgres_experiment_filter("tag.`k'); DROP TABLE runs; --` = 'v'").unwrap();
+        assert!(!sql.contains("DROP"));
+        assert!(sql.contains("experiment_tags.key = $1 AND experiment_tags.value = $2"));
+    }
+
+    #[test]
+    fn test_numeric_parameter_types() {
+        let (_, expression) = parse_filter("metrics.m > 1 AND metrics.m < 2.5").unwrap();
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("");
+        push_run_filter(&mut query_builder, expression.as_ref().unwrap()).unwrap();
+        let sql = query_builder.build().sql().to_string();
+        assert!(sql.contains("latest_metrics.value > $2::BIGINT"));
+        assert!(sql.contains("latest_metrics.value < $4::DOUBLE PRECISION"));
+    }
+
+    #[test]
+    fn test_ilike() {
+        let sql = sqlite_experiment_filter("name ILIKE '%A%'").unwrap();
+        assert_eq!(
+            sql,
+            "WHERE TRUE AND (LOWER(experiments.name) LIKE LOWER(?))"
+        );
+        let sql = postgres_experiment_filter("name ILIKE '%A%'").unwrap();
+        assert_eq!(sql, "WHERE TRUE AND (experiments.name ILIKE $1)");
+    }
+
+    #[test]
+    fn test_null_checks() {
+        let sql = sqlite_experiment_filter("tag.owner IS NULL AND name IS NOT NULL").unwrap();
+        assert_eq!(
+            sql,
+            "WHERE TRUE AND ((NOT EXISTS (SELECT 1 FROM experiment_tags \
+             WHERE experiment_tags.experiment_id = experiments.experiment_id \
+             AND experiment_tags.key = ?)) AND (experiments.name IS NOT NULL))"
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let sql = postgres_experiment_filter(
+            "name = 'a' OR name = 'b' AND NOT (name = 'c' OR experiment_id = 1)",
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "WHERE TRUE AND ((experiments.name = $1) OR ((experiments.name = $2) AND \
+             (NOT ((experiments.name = $3) OR (experiments.experiment_id = $4::BIGINT)))))"
+        );
+    }
+
+    #[test]
+    fn test_attribute_keys_are_whitelisted() {
+        for filter in [
+            "attribute.`name = name OR 1` = 'a'",
+            "attribute.`name; DROP TABLE experiments; --` = 'a'",
+            "attribute.sqlite_master = 'a'",
+        ] {
+            assert!(matches!(
+                sqlite_experiment_filter(filter),
+                Err(MlflowError::InvalidParameter(_))
+            ));
+        }
+        let (_, expression) = parse_filter("attribute.`run_uuid OR 1=1` = 'a'").unwrap();
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("");
+        assert!(matches!(
+            push_run_filter(&mut query_builder, expression.as_ref().unwrap()),
+            Err(MlflowError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_order_by() {
+        let ob = parse_order_by_complete("name DESC").unwrap();
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("");
+        push_experiment_order_by(&mut query_builder, &[ob]).unwrap();
+        assert_eq!(
+            query_builder.build().sql(),
+            " ORDER BY experiments.name DESC"
+        );
+
+        let ob = parse_order_by_complete("`name; DROP TABLE experiments`").unwrap();
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("");
+        assert!(matches!(
+            push_experiment_order_by(&mut query_builder, &[ob]),
+            Err(MlflowError::InvalidParameter(_))
+        ));
+
+        let ob = parse_order_by_complete("tag.`x') DESC; --`").unwrap();
+        let obs = [ob];
+        let mut query_builder: QueryBuilder<Postgres> =
+            QueryBuilder::new("SELECT runs.* FROM runs");
+        push_run_order_by_joins(&mut query_builder, &obs);
+        push_run_order_by(&mut query_builder, &obs).unwrap();
+        let sql = query_builder.build().sql().to_string();
+        assert!(sql.contains("order_0.key = $1"));
+        assert!(!sql.contains("--"));
+    }
+}
@@ -1,33 +1,65 @@
+use crate::config::PoolConfig;
 use crate::entities::{
-    Experiment, ExperimentTag, Run, RunData, RunInfo, SqlExperiment, SqlExperimentTag, SqlRun,
+    Experiment, ExperimentTag, Metric, MetricWithRunId, Param, Run, RunData, RunInfo, RunStatus,
+    RunTag, SqlExperiment, SqlExperimentTag, SqlMetric, SqlParam, SqlRun, SqlRunTag, ViewType,
 };
 use crate::env::{DEFAULT_ARTIFACT_ROOT, MLFLOW_TRACKING_URI};
-use crate::parser::common::{Entity, Identifier};
-use crate::parser::filter::parse_filter;
-use crate::parser::order_by::{parse_order_by, OrderBy, OrderByDirection};
 use crate::stores::tracking::error::MlflowError;
-use crate::stores::tracking::Store;
+use crate::stores::tracking::query::{
+    push_experiment_filter, push_experiment_order_by, push_run_filter, push_run_order_by,
+    push_run_order_by_joins,
+};
+use crate::stores::tracking::{
+    check_max_results, check_schema_version, decode_page_token, experiment_name_conflict,
+    experiment_not_found, group_by_run, paginate, parse_experiment_filter, parse_experiment_id,
+    parse_experiment_ids, parse_experiment_order_by, parse_run_filter, parse_run_order_by,
+    prepare_batch, run_not_found, validate_bulk_interval_request, validate_max_results, PagedList,
+    PreparedBatch, Store, RUN_NAME_TAG,
+};
+use crate::utils::{current_time_millis, random_run_id};
 use async_trait::async_trait;
-use sqlx::sqlite::{Sqlite, SqlitePoolOptions};
-use sqlx::{Pool, QueryBuilder};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{FromRow, Pool, QueryBuilder, Transaction};
 use std::collections::HashMap;
 use std::env;
+use std::str::FromStr;
+
+// Keeps the number of bound variables well below SQLITE_MAX_VARIABLE_NUMBER.
+const MAX_RUN_UUIDS_PER_QUERY: usize = 500;
+
+fn push_run_uuids<'a>(query_builder: &mut QueryBuilder<'a, Sqlite>, run_uuids: &[&'a str]) {
+    let mut separated = query_builder.separated(", ");
+    for run_uuid in run_uuids {
+        separated.push_bind(*run_uuid);
+    }
+}
 
 pub struct SqliteStore {
     pub connection: Pool<Sqlite>,
     pub default_artifact_root: String,
 }
 
-async fn get_connection_pool(db_uri: &str) -> Result<Pool<Sqlite>, sqlx::Error> {
-    Ok(SqlitePoolOptions::new().connect(db_uri).await?)
+async fn get_connection_pool(
+    db_uri: &str,
+    pool_config: &PoolConfig,
+) -> Result<Pool<Sqlite>, sqlx::Error> {
+    SqlitePoolOptions::new()
+        .max_connections(pool_config.max_connections)
+        .connect_timeout(pool_config.acquire_timeout)
+        .idle_timeout(pool_config.idle_timeout)
+        .connect_with(SqliteConnectOptions::from_str(db_uri)?.create_if_missing(true))
+        .await
 }
 
+#[cfg(test)]
 async fn get_connection_pool_from_env() -> Result<Pool<Sqlite>, sqlx::Error> {
     let db_uri = env::var(MLFLOW_TRACKING_URI)
         .unwrap_or_else(|_| panic!("{} must be set", MLFLOW_TRACKING_URI));
-    get_connection_pool(&db_uri).await
+    get_connection_pool(&db_uri, &PoolConfig::default()).await
 }
 
+#[cfg(test)]
 async fn initialize_database() -> Result<(), sqlx::Error> {
     let connection = get_connection_pool_from_env().await?;
     let mut tx = connection.begin().await?;
@@ -49,12 +81,51 @@ async fn initialize_database() -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// Creates the MLflow schema in an empty database, applies any pending migrations to a database
+/// created by this server, and refuses a database at an unknown schema version. Like MLflow, it
+/// then creates the Default experiment if there are no experiments.
+async fn initialize_schema(
+    connection: &Pool<Sqlite>,
+    default_artifact_root: &str,
+) -> Result<(), MlflowError> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        r#"SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'"#,
+    )
+    .fetch_all(connection)
+    .await?;
+    if tables.is_empty() || tables.iter().any(|t| t == "_sqlx_migrations") {
+        MIGRATOR.run(connection).await?;
+    } else if !tables.iter().any(|t| t == "alembic_version") {
+        return check_schema_version(None);
+    }
+    let version: Option<String> = sqlx::query_scalar(r#"SELECT version_num FROM alembic_version"#)
+        .fetch_optional(connection)
+        .await?;
+    check_schema_version(version)?;
+    sqlx::query(
+        r#"
+        INSERT INTO experiments (experiment_id, name, artifact_location, lifecycle_stage)
+        SELECT 0, 'Default', $1, 'active'
+        WHERE NOT EXISTS (SELECT 1 FROM experiments)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(format!("{}/0", default_artifact_root))
+    .execute(connection)
+    .await?;
+    Ok(())
+}
+
 impl SqliteStore {
     pub async fn new(
         db_uri: &str,
         default_artifact_root: &str,
+        pool_config: &PoolConfig,
     ) -> Result<SqliteStore, MlflowError> {
-        let connection = get_connection_pool(db_uri).await?;
+        let connection = get_connection_pool(db_uri, pool_config).await?;
+        initialize_schema(&connection, default_artifact_root).await?;
         Ok(SqliteStore {
             connection,
             default_artifact_root: default_artifact_root.to_string(),
@@ -66,11 +137,7 @@ impl SqliteStore {
             .unwrap_or_else(|_| panic!("{} must be set", MLFLOW_TRACKING_URI));
         let default_artifact_root = env::var(DEFAULT_ARTIFACT_ROOT)
             .unwrap_or_else(|_| panic!("{} must be set", DEFAULT_ARTIFACT_ROOT));
-        let connection = get_connection_pool(&db_uri).await?;
-        Ok(SqliteStore {
-            connection,
-            default_artifact_root,
-        })
+        SqliteStore::new(&db_uri, &default_artifact_root, &PoolConfig::default()).await
     }
 
     async fn get_experiment_tags(
@@ -81,8 +148,7 @@ impl SqliteStore {
             sqlx::query_as(r#"SELECT * FROM experiment_tags WHERE experiment_id = $1"#)
                 .bind(experiment_id)
                 .fetch_all(&self.connection)
-                .await
-                .unwrap();
+                .await?;
         Ok(tags
             .into_iter()
             .map(|t| ExperimentTag {
@@ -91,6 +157,107 @@ impl SqliteStore {
             })
             .collect())
     }
+
+    /// Loads the params, latest metrics and tags of the given runs with one query per table
+    /// (per chunk of runs) rather than one query per run.
+    async fn get_run_data(
+        &self,
+        run_uuids: &[&str],
+    ) -> Result<HashMap<String, RunData>, MlflowError> {
+        let mut run_data: HashMap<String, RunData> = HashMap::new();
+        for chunk in run_uuids.chunks(MAX_RUN_UUIDS_PER_QUERY) {
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("SELECT * FROM params WHERE run_uuid IN (");
+            push_run_uuids(&mut query_builder, chunk);
+            query_builder.push(") ORDER BY key");
+            let params: Vec<SqlParam> = query_builder
+                .build()
+                .try_map(|row| SqlParam::from_row(&row))
+                .fetch_all(&self.connection)
+                .await?;
+            for p in params {
+                run_data
+                    .entry(p.run_uuid.clone())
+                    .or_default()
+                    .params
+                    .push(p.into());
+            }
+
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("SELECT * FROM latest_metrics WHERE run_uuid IN (");
+            push_run_uuids(&mut query_builder, chunk);
+            query_builder.push(") ORDER BY key");
+            let metrics: Vec<SqlMetric> = query_builder
+                .build()
+                .try_map(|row| SqlMetric::from_row(&row))
+                .fetch_all(&self.connection)
+                .await?;
+            for m in metrics {
+                run_data
+                    .entry(m.run_uuid.clone())
+                    .or_default()
+                    .metrics
+                    .push(m.into());
+            }
+
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("SELECT * FROM tags WHERE run_uuid IN (");
+            push_run_uuids(&mut query_builder, chunk);
+            query_builder.push(") ORDER BY key");
+            let tags: Vec<SqlRunTag> = query_builder
+                .build()
+                .try_map(|row| SqlRunTag::from_row(&row))
+                .fetch_all(&self.connection)
+                .await?;
+            for t in tags {
+                run_data
+                    .entry(t.run_uuid.clone())
+                    .or_default()
+                    .tags
+                    .push(t.into());
+            }
+        }
+        Ok(run_data)
+    }
+
+    /// Loads a run that is about to be written to within `tx`, failing unless it is active. SQLite
+    /// serializes write transactions, so a write in `tx` fails if the run was deleted after this.
+    async fn get_active_run(
+        tx: &mut Transaction<'_, Sqlite>,
+        run_id: &str,
+    ) -> Result<SqlRun, MlflowError> {
+        let run: SqlRun = sqlx::query_as(r#"SELECT * FROM runs WHERE run_uuid = $1"#)
+            .bind(run_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| run_not_found(run_id))?;
+        if run.lifecycle_stage != "active" {
+            return Err(MlflowError::InvalidParameter(format!(
+                "The run {} must be in the 'active' state. Current state is {}.",
+                run_id, run.lifecycle_stage
+            )));
+        }
+        Ok(run)
+    }
+
+    async fn get_active_experiment(
+        &self,
+        experiment_id: &str,
+    ) -> Result<SqlExperiment, MlflowError> {
+        let experiment: SqlExperiment =
+            sqlx::query_as(r#"SELECT * FROM experiments WHERE experiment_id = $1"#)
+                .bind(parse_experiment_id(experiment_id)?)
+                .fetch_optional(&self.connection)
+                .await?
+                .ok_or_else(|| experiment_not_found(experiment_id))?;
+        if experiment.lifecycle_stage != "active" {
+            return Err(MlflowError::InvalidParameter(format!(
+                "The experiment {} must be in the 'active' state. Current state is {}.",
+                experiment_id, experiment.lifecycle_stage
+            )));
+        }
+        Ok(experiment)
+    }
 }
 
 #[async_trait]
@@ -99,357 +266,652 @@ impl Store for SqliteStore {
         self.connection.close().await;
     }
 
-    async fn search_runs(&self, experiment_ids: Vec<&str>) -> Result<Vec<Run>, MlflowError> {
-        let experiment_id_filter = format!("experiment_id IN ({})", experiment_ids.join(","));
-        let query = format!(r#"SELECT * FROM runs WHERE {}"#, experiment_id_filter);
-        let runs: Vec<SqlRun> = sqlx::query_as(query.as_str())
+    async fn search_runs(
+        &self,
+        experiment_ids: Vec<&str>,
+        filter: Option<&str>,
+        run_view_type: ViewType,
+        order_by: Option<Vec<&str>>,
+        max_results: Option<i64>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Run>, MlflowError> {
+        let experiment_ids = parse_experiment_ids(experiment_ids)?;
+        let expression = parse_run_filter(filter)?;
+        let order_by = parse_run_order_by(order_by)?;
+        let max_results = validate_max_results(max_results)?;
+        let offset = decode_page_token(page_token)?;
+        if experiment_ids.is_empty() {
+            return Ok(PagedList {
+                items: vec![],
+                next_page_token: None,
+            });
+        }
+
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT runs.* FROM runs");
+        push_run_order_by_joins(&mut query_builder, &order_by);
+        query_builder.push(" WHERE runs.experiment_id IN (");
+        let mut separated = query_builder.separated(", ");
+        for experiment_id in experiment_ids {
+            separated.push_bind(experiment_id);
+        }
+        query_builder.push(") AND runs.lifecycle_stage IN (");
+        let mut separated = query_builder.separated(", ");
+        for lifecycle_stage in run_view_type.lifecycle_stages() {
+            separated.push_bind(lifecycle_stage);
+        }
+        query_builder.push(")");
+        if let Some(expression) = &expression {
+            query_builder.push(" AND ");
+            push_run_filter(&mut query_builder, expression)?;
+        }
+        push_run_order_by(&mut query_builder, &order_by)?;
+        query_builder.push(" LIMIT ");
+        query_builder.push_bind(max_results + 1);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+        let runs: Vec<SqlRun> = query_builder
+            .build()
+            .try_map(|row| SqlRun::from_row(&row))
             .fetch_all(&self.connection)
             .await?;
-        Ok(runs
-            .into_iter()
-            .map(|r| Run {
-                info: RunInfo {
-                    name: r.name,
-                    run_uuid: r.run_uuid.clone(),
-                    run_id: r.run_uuid,
-                    experiment_id: r.experiment_id.to_string(),
-                    user_id: r.user_id,
-                    status: r.status,
-                    start_time: r.start_time,
-                    end_time: r.end_time,
-                    lifecycle_stage: r.lifecycle_stage,
-                    artifact_uri: r.artifact_uri,
-                },
-                data: RunData {
-                    params: vec![],
-                    metrics: vec![],
-                    tags: vec![],
-                },
-            })
-            .collect())
+        let runs = paginate(runs, offset, Some(max_results));
+        let mut run_data = self
+            .get_run_data(
+                &runs
+                    .items
+                    .iter()
+                    .map(|r| r.run_uuid.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .await?;
+        Ok(PagedList {
+            items: runs
+                .items
+                .into_iter()
+                .map(|r| Run {
+                    data: run_data.remove(&r.run_uuid).unwrap_or_default(),
+                    info: r.into(),
+                })
+                .collect(),
+            next_page_token: runs.next_page_token,
+        })
     }
 
     async fn get_run(&self, run_id: &str) -> Result<Run, MlflowError> {
         let r: SqlRun = sqlx::query_as(r#"SELECT * FROM runs WHERE run_uuid = $1"#)
             .bind(run_id)
-            .fetch_one(&self.connection)
-            .await?;
+            .fetch_optional(&self.connection)
+            .await?
+            .ok_or_else(|| run_not_found(run_id))?;
+        let data = self
+            .get_run_data(&[r.run_uuid.as_str()])
+            .await?
+            .remove(&r.run_uuid)
+            .unwrap_or_default();
         Ok(Run {
-            info: RunInfo {
-                name: r.name,
-                run_uuid: r.run_uuid.clone(),
-                run_id: r.run_uuid,
-                experiment_id: r.experiment_id.to_string(),
-                user_id: r.user_id,
-                status: r.status,
-                start_time: r.start_time,
-                end_time: r.end_time,
-                lifecycle_stage: r.lifecycle_stage,
-                artifact_uri: r.artifact_uri,
-            },
-            data: RunData {
-                params: vec![],
-                metrics: vec![],
-                tags: vec![],
-            },
+            info: r.into(),
+            data,
         })
     }
 
-    async fn list_experiments(&self) -> Result<Vec<Experiment>, MlflowError> {
-        let sql_experiments: Vec<SqlExperiment> = sqlx::query_as(r#"SELECT * FROM experiments"#)
-            .fetch_all(&self.connection)
-            .await?;
-        let mut experiments: Vec<Experiment> = vec![];
-        for e in sql_experiments {
-            experiments.push(Experiment {
-                experiment_id: e.experiment_id.to_string(),
-                name: e.name,
-                artifact_location: e.artifact_location,
-                lifecycle_stage: e.lifecycle_stage,
-                tags: self.get_experiment_tags(e.experiment_id).await?,
-            })
-        }
-
-        Ok(experiments)
-    }
-
-    async fn search_experiments(
+    async fn create_run(
         &self,
-        max_results: Option<i64>,
-        filter_string: Option<&str>,
-        order_by: Option<Vec<&str>>,
-    ) -> Result<Vec<Experiment>, MlflowError> {
-        let (remaining, comparisons) = parse_filter(filter_string.unwrap_or("").trim())
-            .map_err(|e| MlflowError::InvalidParameter(e.to_string()))?;
-
-        if !remaining.is_empty() {
+        experiment_id: &str,
+        user_id: Option<&str>,
+        start_time: Option<i64>,
+        run_name: Option<&str>,
+        tags: Option<Vec<&RunTag>>,
+    ) -> Result<Run, MlflowError> {
+        let experiment_id = parse_experiment_id(experiment_id)?;
+        let experiment: SqlExperiment =
+            sqlx::query_as(r#"SELECT * FROM experiments WHERE experiment_id = $1"#)
+                .bind(experiment_id)
+                .fetch_optional(&self.connection)
+                .await?
+                .ok_or_else(|| experiment_not_found(experiment_id))?;
+        if experiment.lifecycle_stage != "active" {
             return Err(MlflowError::InvalidParameter(format!(
-                "Invalid filter: {}",
-                remaining
+                "The experiment {} must be in the 'active' state. Current state is {}.",
+                experiment_id, experiment.lifecycle_stage
             )));
         }
 
-        if comparisons
-            .iter()
-            .any(|c| !(c.left.entity == Entity::Attribute || c.left.entity == Entity::Tag))
-        {
-            return Err(MlflowError::InvalidParameter(
-                "Experiment search only supports filtering by attribute or tag".to_string(),
-            ));
-        }
-
-        let mut order_by_clauses: Vec<OrderBy> = vec![];
-        for ob in order_by.unwrap_or_else(|| vec![]) {
-            let (remaining, ob) = parse_order_by(ob.trim())
-                .map_err(|e| MlflowError::InvalidParameter(e.to_string()))?;
-
-            if !remaining.is_empty() {
+        let tags = tags.unwrap_or_default();
+        // Fall back to the `mlflow.runName` tag if no run name is given, and vice versa.
+        let run_name_tag = tags.iter().find(|t| t.key == RUN_NAME_TAG);
+        if let (Some(run_name), Some(tag)) = (run_name, run_name_tag) {
+            if run_name != tag.value {
                 return Err(MlflowError::InvalidParameter(format!(
-                    "Invalid order by clause: {}",
-                    remaining
+                    "Both 'run_name' argument and '{}' tag are specified, but with different \
+                     values (run_name='{}', {}='{}').",
+                    RUN_NAME_TAG, run_name, RUN_NAME_TAG, tag.value
                 )));
             }
-
-            if ob.identifier.entity != Entity::Attribute {
-                return Err(MlflowError::InvalidParameter(
-                    "Experiment search only supports ordering by attribute".to_string(),
-                ));
-            }
-            order_by_clauses.push(ob);
         }
+        let run_name = run_name
+            .or_else(|| run_name_tag.map(|t| t.value.as_str()))
+            .unwrap_or("");
+        let run_uuid = random_run_id();
+        let artifact_uri = format!("{}/{}/artifacts", experiment.artifact_location, run_uuid);
 
-        // Add experiment_id to order_by as a tie-breaker if it's not present
-        if !order_by_clauses
-            .iter()
-            .any(|ob| ob.identifier.key == "experiment_id")
-        {
-            order_by_clauses.push(OrderBy {
-                identifier: Identifier {
-                    entity: Entity::Attribute,
-                    key: "experiment_id".to_string(),
-                },
-                ascending: OrderByDirection::Ascending,
-            });
-        }
-        let order_by = order_by_clauses
-            .into_iter()
-            .map(|ob| format!("{} {}", ob.identifier.key, ob.ascending))
-            .collect::<Vec<_>>()
-            .join(", ");
+        let mut tx = self.connection.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO runs (
+                run_uuid, name, source_type, source_name, entry_point_name, user_id, status,
+                start_time, end_time, source_version, lifecycle_stage, artifact_uri, experiment_id
+            )
+            VALUES ($1, $2, 'UNKNOWN', '', '', $3, $4, $5, NULL, '', 'active', $6, $7)
+            "#,
+        )
+        .bind(&run_uuid)
+        .bind(run_name)
+        .bind(user_id.unwrap_or(""))
+        .bind(RunStatus::Running.to_string())
+        .bind(start_time.unwrap_or_else(current_time_millis))
+        .bind(&artifact_uri)
+        .bind(experiment_id)
+        .execute(&mut tx)
+        .await?;
 
-        let attribute_comparisons = comparisons
-            .iter()
-            .filter(|c| c.left.entity == Entity::Attribute)
-            .collect::<Vec<_>>();
-        let attribute_filter = if attribute_comparisons.is_empty() {
-            "1 = 1".to_string()
+        let run_name_tag = if run_name_tag.is_none() && !run_name.is_empty() {
+            Some(RunTag {
+                key: RUN_NAME_TAG.to_string(),
+                value: run_name.to_string(),
+            })
         } else {
-            attribute_comparisons
-                .iter()
-                .map(|c| format!("{} {} {}", c.left.key, c.operator, c.right))
-                .collect::<Vec<_>>()
-                .join(" AND ")
+            None
         };
+        let tags: Vec<&RunTag> = tags.iter().copied().chain(run_name_tag.as_ref()).collect();
+        if !tags.is_empty() {
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("INSERT INTO tags (key, value, run_uuid) ");
 
-        let tag_comparisons = comparisons
-            .iter()
-            .filter(|c| c.left.entity == Entity::Tag)
-            .collect::<Vec<_>>();
-
-        let query = if tag_comparisons.is_empty() {
-            format!(
-                r#"
-                SELECT * FROM experiments
-                WHERE {}
-                ORDER BY {}
-                LIMIT $1
-                "#,
-                attribute_filter, order_by
-            )
-        } else {
-            let mut tag_filters: HashMap<&str, Vec<String>> = HashMap::new();
-            for tag_comp in tag_comparisons {
-                if tag_filters.contains_key(tag_comp.left.key.as_str()) {
-                    tag_filters
-                        .get_mut(tag_comp.left.key.as_str())
-                        .unwrap()
-                        .push(format!("value {} {}", tag_comp.operator, tag_comp.right));
-                } else {
-                    tag_filters.insert(
-                        tag_comp.left.key.as_str(),
-                        vec![
-                            format!("key = '{}'", tag_comp.left.key),
-                            format!("value {} {}", tag_comp.operator, tag_comp.right),
-                        ],
-                    );
-                }
-            }
-            let num_unique_tags = tag_filters.len();
-            let filtered_tags = format!(
-                r#"
-                SELECT experiment_id
-                FROM experiment_tags
-                WHERE {}
-                GROUP BY experiment_id
-                HAVING COUNT(*) >= {}
-                "#,
-                tag_filters
-                    .iter()
-                    .map(|(_, v)| { format!("({})", v.join(" AND ")) })
-                    .collect::<Vec<_>>()
-                    .join(" OR "),
-                num_unique_tags,
-            );
+            query_builder.push_values(tags, |mut b, tag| {
+                b.push_bind(&tag.key)
+                    .push_bind(&tag.value)
+                    .push_bind(&run_uuid);
+            });
 
-            format!(
-                r#"
-                SELECT *
-                FROM experiments
-                INNER JOIN ({}) ft ON ft.experiment_id = experiments.experiment_id
-                WHERE {}
-                ORDER BY {}
-                LIMIT $1
-                "#,
-                filtered_tags, attribute_filter, order_by
-            )
-        };
-        let sql_experiments: Vec<SqlExperiment> = sqlx::query_as(query.as_str())
-            .bind(max_results.unwrap_or(-1))
-            .fetch_all(&self.connection)
-            .await?;
-        let mut experiments: Vec<Experiment> = vec![];
-        for e in sql_experiments {
-            experiments.push(Experiment {
-                experiment_id: e.experiment_id.to_string(),
-                name: e.name,
-                artifact_location: e.artifact_location,
-                lifecycle_stage: e.lifecycle_stage,
-                tags: self.get_experiment_tags(e.experiment_id).await?,
-            })
+            let query = query_builder.build();
+            query.execute(&mut tx).await?;
         }
-        Ok(experiments)
-    }
+        tx.commit().await?;
 
-    async fn get_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError> {
-        let experiment: SqlExperiment =
-            sqlx::query_as(r#"SELECT * FROM experiments WHERE experiment_id = $1"#)
-                .bind(experiment_id.parse::<i32>().unwrap())
-                .fetch_one(&self.connection)
-                .await?;
-        Ok(Experiment {
-            experiment_id: experiment.experiment_id.to_string(),
-            name: experiment.name,
-            artifact_location: experiment.artifact_location,
-            lifecycle_stage: experiment.lifecycle_stage,
-            tags: self.get_experiment_tags(experiment.experiment_id).await?,
-        })
+        self.get_run(&run_uuid).await
     }
 
-    async fn create_experiment(
+    async fn update_run(
         &self,
-        name: &str,
-        artifact_location: Option<&str>,
-        tags: Option<Vec<&ExperimentTag>>,
-    ) -> Result<Experiment, MlflowError> {
+        run_id: &str,
+        status: Option<RunStatus>,
+        end_time: Option<i64>,
+        run_name: Option<&str>,
+    ) -> Result<RunInfo, MlflowError> {
         let mut tx = self.connection.begin().await?;
-        let experiment = if let Some(artifact_location) = artifact_location {
-            let experiment: SqlExperiment = sqlx::query_as(
-                r#"
-                INSERT INTO experiments (name, artifact_location, lifecycle_stage)
-                VALUES ($1, $2, 'active')
-                RETURNING *
-                "#,
-            )
-            .bind(name)
-            .bind(artifact_location)
-            .fetch_one(&mut tx)
-            .await?;
-            experiment
-        } else {
-            let experiment: SqlExperiment = sqlx::query_as(
-                r#"
-                INSERT INTO experiments (name, artifact_location, lifecycle_stage)
-                VALUES ($1, '', 'active')
-                RETURNING *
-                "#,
-            )
-            .bind(name)
-            .fetch_one(&mut tx)
-            .await?;
-
-            let artifact_loc = format!(
-                "{}/{}",
-                self.default_artifact_root, experiment.experiment_id
-            );
-            let experiment: SqlExperiment = sqlx::query_as(
+        Self::get_active_run(&mut tx, run_id).await?;
+        let run: SqlRun = sqlx::query_as(
+            r#"
+            UPDATE runs
+            SET status = COALESCE($1, status),
+                end_time = COALESCE($2, end_time),
+                name = COALESCE($3, name)
+            WHERE run_uuid = $4
+            RETURNING *
+            "#,
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(end_time)
+        .bind(run_name)
+        .bind(run_id)
+        .fetch_one(&mut tx)
+        .await?;
+        if let Some(run_name) = run_name {
+            sqlx::query(
                 r#"
-                UPDATE experiments SET artifact_location = $1 WHERE name = $2
-                RETURNING *
+                INSERT INTO tags (key, value, run_uuid) VALUES ($1, $2, $3)
+                ON CONFLICT (key, run_uuid) DO UPDATE SET value = excluded.value
                 "#,
             )
-            .bind(&artifact_loc)
-            .bind(name)
-            .fetch_one(&mut tx)
+            .bind(RUN_NAME_TAG)
+            .bind(run_name)
+            .bind(run_id)
+            .execute(&mut tx)
             .await?;
-            experiment
-        };
-
-        if let Some(tags) = tags {
-            let mut query_builder: QueryBuilder<Sqlite> =
-                QueryBuilder::new("INSERT INTO experiment_tags (experiment_id, key, value) ");
-
-            query_builder.push_values(tags, |mut b, tag| {
-                b.push_bind(experiment.experiment_id)
-                    .push_bind(&tag.key)
-                    .push_bind(&tag.value);
-            });
-
-            let query = query_builder.build();
-            query.execute(&mut tx).await?;
-        };
+        }
         tx.commit().await?;
-
-        Ok(Experiment {
-            experiment_id: experiment.experiment_id.to_string(),
-            name: experiment.name,
-            artifact_location: experiment.artifact_location,
-            lifecycle_stage: experiment.lifecycle_stage,
-            tags: self.get_experiment_tags(experiment.experiment_id).await?,
-        })
+        Ok(run.into())
     }
 
-    async fn delete_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError> {
+    async fn delete_run(&self, run_id: &str) -> Result<Run, MlflowError> {
         sqlx::query(
-            r#"UPDATE experiments SET lifecycle_stage = 'deleted' WHERE experiment_id = $1"#,
+            r#"UPDATE runs SET lifecycle_stage = 'deleted', deleted_time = $1 WHERE run_uuid = $2"#,
         )
-        .bind(experiment_id.parse::<i32>().unwrap())
+        .bind(current_time_millis())
+        .bind(run_id)
         .execute(&self.connection)
         .await?;
-        let experiment: SqlExperiment =
-            sqlx::query_as(r#"SELECT * FROM experiments WHERE experiment_id = $1"#)
-                .bind(experiment_id.parse::<i32>().unwrap())
-                .fetch_one(&self.connection)
-                .await?;
-        Ok(Experiment {
-            experiment_id: experiment.experiment_id.to_string(),
-            name: experiment.name,
-            artifact_location: experiment.artifact_location,
-            lifecycle_stage: experiment.lifecycle_stage,
-            tags: self.get_experiment_tags(experiment.experiment_id).await?,
-        })
+        self.get_run(run_id).await
     }
 
-    async fn restore_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError> {
-        let experiment: SqlExperiment = sqlx::query_as(
+    async fn restore_run(&self, run_id: &str) -> Result<Run, MlflowError> {
+        sqlx::query(
             r#"
-            UPDATE experiments SET lifecycle_stage = 'active'
-            WHERE experiment_id = $1
-            RETURNING *
+            UPDATE runs SET lifecycle_stage = 'active', deleted_time = NULL WHERE run_uuid = $1
             "#,
         )
-        .bind(experiment_id.parse::<i32>().unwrap())
-        .fetch_one(&self.connection)
+        .bind(run_id)
+        .execute(&self.connection)
+        .await?;
+        self.get_run(run_id).await
+    }
+
+    async fn log_metric(&self, run_id: &str, metric: &Metric) -> Result<(), MlflowError> {
+        // NaN can't be stored in every backend, so it's flagged with `is_nan` instead.
+        let is_nan = metric.value.is_nan();
+        let value = if is_nan { 0.0 } else { metric.value };
+        let mut tx = self.connection.begin().await?;
+        Self::get_active_run(&mut tx, run_id).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO metrics (key, value, timestamp, step, is_nan, run_uuid)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(&metric.key)
+        .bind(value)
+        .bind(metric.timestamp)
+        .bind(metric.step)
+        .bind(is_nan)
+        .bind(run_id)
+        .execute(&mut tx)
+        .await?;
+        // Keep latest_metrics in sync the same way the Python server does: the metric with
+        // the largest (step, timestamp, value) wins.
+        sqlx::query(
+            r#"
+            INSERT INTO latest_metrics (key, value, timestamp, step, is_nan, run_uuid)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (key, run_uuid) DO UPDATE
+            SET value = excluded.value,
+                timestamp = excluded.timestamp,
+                step = excluded.step,
+                is_nan = excluded.is_nan
+            WHERE (excluded.step, excluded.timestamp, excluded.value)
+                >= (latest_metrics.step, latest_metrics.timestamp, latest_metrics.value)
+            "#,
+        )
+        .bind(&metric.key)
+        .bind(value)
+        .bind(metric.timestamp)
+        .bind(metric.step)
+        .bind(is_nan)
+        .bind(run_id)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn log_param(&self, run_id: &str, param: &Param) -> Result<(), MlflowError> {
+        let mut tx = self.connection.begin().await?;
+        Self::get_active_run(&mut tx, run_id).await?;
+        let existing: Option<(String,)> =
+            sqlx::query_as(r#"SELECT value FROM params WHERE run_uuid = $1 AND key = $2"#)
+                .bind(run_id)
+                .bind(&param.key)
+                .fetch_optional(&mut tx)
+                .await?;
+        match existing {
+            Some((value,)) if value != param.value => {
+                return Err(MlflowError::InvalidParameter(format!(
+                    "Changing param values is not allowed. Param with key='{}' was already \
+                     logged with value='{}' for run ID='{}'. Attempted logging new value '{}'.",
+                    param.key, value, run_id, param.value
+                )));
+            }
+            Some(_) => {}
+            None => {
+                sqlx::query(r#"INSERT INTO params (key, value, run_uuid) VALUES ($1, $2, $3)"#)
+                    .bind(&param.key)
+                    .bind(&param.value)
+                    .bind(run_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn set_tag(&self, run_id: &str, tag: &RunTag) -> Result<(), MlflowError> {
+        let mut tx = self.connection.begin().await?;
+        Self::get_active_run(&mut tx, run_id).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO tags (key, value, run_uuid) VALUES ($1, $2, $3)
+            ON CONFLICT (key, run_uuid) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(&tag.key)
+        .bind(&tag.value)
+        .bind(run_id)
+        .execute(&mut tx)
+        .await?;
+        if tag.key == RUN_NAME_TAG {
+            sqlx::query(r#"UPDATE runs SET name = $1 WHERE run_uuid = $2"#)
+                .bind(&tag.value)
+                .bind(run_id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_tag(&self, run_id: &str, key: &str) -> Result<(), MlflowError> {
+        let mut tx = self.connection.begin().await?;
+        Self::get_active_run(&mut tx, run_id).await?;
+        let result = sqlx::query(r#"DELETE FROM tags WHERE run_uuid = $1 AND key = $2"#)
+            .bind(run_id)
+            .bind(key)
+            .execute(&mut tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(MlflowError::ResourceDoesNotExist(format!(
+                "No tag with name: {} in run with id {}",
+                key, run_id
+            )));
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<&Metric>,
+        params: Vec<&Param>,
+        tags: Vec<&RunTag>,
+    ) -> Result<(), MlflowError> {
+        let mut tx = self.connection.begin().await?;
+        Self::get_active_run(&mut tx, run_id).await?;
+        let existing_params: Vec<(String, String)> =
+            sqlx::query_as(r#"SELECT key, value FROM params WHERE run_uuid = $1"#)
+                .bind(run_id)
+                .fetch_all(&mut tx)
+                .await?;
+        let PreparedBatch {
+            params,
+            tags,
+            latest_metrics,
+        } = prepare_batch(run_id, &metrics, params, tags, existing_params)?;
+
+        if !params.is_empty() {
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("INSERT INTO params (key, value, run_uuid) ");
+            query_builder.push_values(params, |mut b, param| {
+                b.push_bind(&param.key)
+                    .push_bind(&param.value)
+                    .push_bind(run_id);
+            });
+            query_builder.build().execute(&mut tx).await?;
+        }
+
+        if !tags.is_empty() {
+            if let Some(tag) = tags.iter().find(|t| t.key == RUN_NAME_TAG) {
+                sqlx::query(r#"UPDATE runs SET name = $1 WHERE run_uuid = $2"#)
+                    .bind(&tag.value)
+                    .bind(run_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("INSERT INTO tags (key, value, run_uuid) ");
+            query_builder.push_values(tags, |mut b, tag| {
+                b.push_bind(&tag.key)
+                    .push_bind(&tag.value)
+                    .push_bind(run_id);
+            });
+            query_builder.push(" ON CONFLICT (key, run_uuid) DO UPDATE SET value = excluded.value");
+            query_builder.build().execute(&mut tx).await?;
+        }
+
+        if !metrics.is_empty() {
+            let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+                "INSERT INTO metrics (key, value, timestamp, step, is_nan, run_uuid) ",
+            );
+            query_builder.push_values(metrics, |mut b, metric| {
+                let is_nan = metric.value.is_nan();
+                b.push_bind(&metric.key)
+                    .push_bind(if is_nan { 0.0 } else { metric.value })
+                    .push_bind(metric.timestamp)
+                    .push_bind(metric.step)
+                    .push_bind(is_nan)
+                    .push_bind(run_id);
+            });
+            query_builder.push(" ON CONFLICT DO NOTHING");
+            query_builder.build().execute(&mut tx).await?;
+
+            let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+                "INSERT INTO latest_metrics (key, value, timestamp, step, is_nan, run_uuid) ",
+            );
+            query_builder.push_values(latest_metrics, |mut b, metric| {
+                let is_nan = metric.value.is_nan();
+                b.push_bind(&metric.key)
+                    .push_bind(if is_nan { 0.0 } else { metric.value })
+                    .push_bind(metric.timestamp)
+                    .push_bind(metric.step)
+                    .push_bind(is_nan)
+                    .push_bind(run_id);
+            });
+            query_builder.push(
+                r#"
+                ON CONFLICT (key, run_uuid) DO UPDATE
+                SET value = excluded.value,
+                    timestamp = excluded.timestamp,
+                    step = excluded.step,
+                    is_nan = excluded.is_nan
+                WHERE (excluded.step, excluded.timestamp, excluded.value)
+                    >= (latest_metrics.step, latest_metrics.timestamp, latest_metrics.value)
+                "#,
+            );
+            query_builder.build().execute(&mut tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_metric_history(
+        &self,
+        run_id: &str,
+        metric_key: &str,
+        max_results: Option<i64>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Metric>, MlflowError> {
+        let max_results = max_results.map(check_max_results).transpose()?;
+        let offset = decode_page_token(page_token)?;
+        sqlx::query(r#"SELECT run_uuid FROM runs WHERE run_uuid = $1"#)
+            .bind(run_id)
+            .fetch_optional(&self.connection)
+            .await?
+            .ok_or_else(|| run_not_found(run_id))?;
+        let metrics: Vec<SqlMetric> = sqlx::query_as(
+            r#"
+            SELECT * FROM metrics
+            WHERE run_uuid = $1 AND key = $2
+            ORDER BY step, timestamp, value
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(run_id)
+        .bind(metric_key)
+        .bind(max_results.map_or(-1, |m| m + 1))
+        .bind(offset)
+        .fetch_all(&self.connection)
+        .await?;
+        let metrics = paginate(metrics, offset, max_results);
+        Ok(PagedList {
+            items: metrics.items.into_iter().map(|m| m.into()).collect(),
+            next_page_token: metrics.next_page_token,
+        })
+    }
+
+    async fn get_metric_history_bulk_interval(
+        &self,
+        run_ids: Vec<&str>,
+        metric_key: &str,
+        start_step: Option<i64>,
+        end_step: Option<i64>,
+        max_results: Option<i64>,
+    ) -> Result<Vec<MetricWithRunId>, MlflowError> {
+        let max_results =
+            validate_bulk_interval_request(&run_ids, start_step, end_step, max_results)?;
+        // Numbers the distinct steps of each run and keeps `max_results` evenly spaced ones. The
+        // first step is always kept, and so is the last one unless `max_results` is 1.
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+            r#"
+            WITH sampled_steps AS (
+                SELECT
+                    run_uuid,
+                    step,
+                    ROW_NUMBER() OVER (PARTITION BY run_uuid ORDER BY step) AS step_number,
+                    COUNT(*) OVER (PARTITION BY run_uuid) AS step_count
+                FROM (
+                    SELECT DISTINCT run_uuid, step FROM metrics
+            WHERE run_uuid IN ("#,
+        );
+        push_run_uuids(&mut query_builder, &run_ids);
+        query_builder.push(") AND key = ");
+        query_builder.push_bind(metric_key);
+        if let Some(start_step) = start_step {
+            query_builder.push(" AND step >= ");
+            query_builder.push_bind(start_step);
+        }
+        if let Some(end_step) = end_step {
+            query_builder.push(" AND step <= ");
+            query_builder.push_bind(end_step);
+        }
+        query_builder.push(
+            r#"
+                ) AS steps
+            )
+            SELECT metrics.* FROM metrics
+            JOIN sampled_steps
+            ON sampled_steps.run_uuid = metrics.run_uuid AND sampled_steps.step = metrics.step
+            WHERE metrics.key = "#,
+        );
+        query_builder.push_bind(metric_key);
+        query_builder
+            .push(" AND (sampled_steps.step_number = 1 OR CASE WHEN sampled_steps.step_count <= ");
+        query_builder.push_bind(max_results);
+        query_builder.push(" THEN 1 = 1 ELSE ((sampled_steps.step_number - 1) * ");
+        query_builder.push_bind(max_results - 1);
+        query_builder.push(") % (sampled_steps.step_count - 1) < ");
+        query_builder.push_bind(max_results - 1);
+        query_builder.push(" END) ORDER BY metrics.step, metrics.timestamp, metrics.value");
+        let metrics: Vec<SqlMetric> = query_builder
+            .build()
+            .try_map(|row| SqlMetric::from_row(&row))
+            .fetch_all(&self.connection)
+            .await?;
+        Ok(group_by_run(
+            &run_ids,
+            metrics.into_iter().map(|m| m.into()).collect(),
+        ))
+    }
+
+    async fn list_experiments(
+        &self,
+        max_results: Option<i64>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Experiment>, MlflowError> {
+        let max_results = validate_max_results(max_results)?;
+        let offset = decode_page_token(page_token)?;
+        let sql_experiments: Vec<SqlExperiment> = sqlx::query_as(
+            r#"SELECT * FROM experiments ORDER BY experiment_id LIMIT $1 OFFSET $2"#,
+        )
+        .bind(max_results + 1)
+        .bind(offset)
+        .fetch_all(&self.connection)
         .await?;
+        let sql_experiments = paginate(sql_experiments, offset, Some(max_results));
+        let mut experiments: Vec<Experiment> = vec![];
+        for e in sql_experiments.items {
+            experiments.push(Experiment {
+                experiment_id: e.experiment_id.to_string(),
+                name: e.name,
+                artifact_location: e.artifact_location,
+                lifecycle_stage: e.lifecycle_stage,
+                tags: self.get_experiment_tags(e.experiment_id).await?,
+            })
+        }
+
+        Ok(PagedList {
+            items: experiments,
+            next_page_token: sql_experiments.next_page_token,
+        })
+    }
+
+    async fn search_experiments(
+        &self,
+        max_results: Option<i64>,
+        filter_string: Option<&str>,
+        view_type: ViewType,
+        order_by: Option<Vec<&str>>,
+        page_token: Option<&str>,
+    ) -> Result<PagedList<Experiment>, MlflowError> {
+        let max_results = validate_max_results(max_results)?;
+        let offset = decode_page_token(page_token)?;
+        let expression = parse_experiment_filter(filter_string)?;
+        let order_by = parse_experiment_order_by(order_by)?;
+
+        let mut query_builder: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT * FROM experiments WHERE experiments.lifecycle_stage IN (");
+        let mut separated = query_builder.separated(", ");
+        for lifecycle_stage in view_type.lifecycle_stages() {
+            separated.push_bind(lifecycle_stage);
+        }
+        query_builder.push(")");
+        if let Some(expression) = &expression {
+            query_builder.push(" AND ");
+            push_experiment_filter(&mut query_builder, expression)?;
+        }
+        push_experiment_order_by(&mut query_builder, &order_by)?;
+        query_builder.push(" LIMIT ");
+        query_builder.push_bind(max_results + 1);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+        let sql_experiments: Vec<SqlExperiment> = query_builder
+            .build()
+            .try_map(|row| SqlExperiment::from_row(&row))
+            .fetch_all(&self.connection)
+            .await?;
+        let sql_experiments = paginate(sql_experiments, offset, Some(max_results));
+        let mut experiments: Vec<Experiment> = vec![];
+        for e in sql_experiments.items {
+            experiments.push(Experiment {
+                experiment_id: e.experiment_id.to_string(),
+                name: e.name,
+                artifact_location: e.artifact_location,
+                lifecycle_stage: e.lifecycle_stage,
+                tags: self.get_experiment_tags(e.experiment_id).await?,
+            })
+        }
+        Ok(PagedList {
+            items: experiments,
+            next_page_token: sql_experiments.next_page_token,
+        })
+    }
+
+    async fn get_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError> {
+        let experiment: SqlExperiment =
+            sqlx::query_as(r#"SELECT * FROM experiments WHERE experiment_id = $1"#)
+                .bind(parse_experiment_id(experiment_id)?)
+                .fetch_optional(&self.connection)
+                .await?
+                .ok_or_else(|| experiment_not_found(experiment_id))?;
         Ok(Experiment {
             experiment_id: experiment.experiment_id.to_string(),
             name: experiment.name,
@@ -459,20 +921,136 @@ impl Store for SqliteStore {
         })
     }
 
-    async fn update_experiment(
+    async fn get_experiment_by_name(
         &self,
-        experiment_id: &str,
-        new_name: &str,
+        experiment_name: &str,
+    ) -> Result<Experiment, MlflowError> {
+        let experiment: SqlExperiment =
+            sqlx::query_as(r#"SELECT * FROM experiments WHERE name = $1"#)
+                .bind(experiment_name)
+                .fetch_optional(&self.connection)
+                .await?
+                .ok_or_else(|| {
+                    MlflowError::ResourceDoesNotExist(format!(
+                        "Could not find experiment with name '{}'",
+                        experiment_name
+                    ))
+                })?;
+        Ok(Experiment {
+            experiment_id: experiment.experiment_id.to_string(),
+            name: experiment.name,
+            artifact_location: experiment.artifact_location,
+            lifecycle_stage: experiment.lifecycle_stage,
+            tags: self.get_experiment_tags(experiment.experiment_id).await?,
+        })
+    }
+
+    async fn create_experiment(
+        &self,
+        name: &str,
+        artifact_location: Option<&str>,
+        tags: Option<Vec<&ExperimentTag>>,
     ) -> Result<Experiment, MlflowError> {
+        let mut tx = self.connection.begin().await?;
+        let experiment = if let Some(artifact_location) = artifact_location {
+            let experiment: SqlExperiment = sqlx::query_as(
+                r#"
+                INSERT INTO experiments (name, artifact_location, lifecycle_stage)
+                VALUES ($1, $2, 'active')
+                RETURNING *
+                "#,
+            )
+            .bind(name)
+            .bind(artifact_location)
+            .fetch_one(&mut tx)
+            .await
+            .map_err(experiment_name_conflict(name))?;
+            experiment
+        } else {
+            let experiment: SqlExperiment = sqlx::query_as(
+                r#"
+                INSERT INTO experiments (name, artifact_location, lifecycle_stage)
+                VALUES ($1, '', 'active')
+                RETURNING *
+                "#,
+            )
+            .bind(name)
+            .fetch_one(&mut tx)
+            .await
+            .map_err(experiment_name_conflict(name))?;
+
+            let artifact_loc = format!(
+                "{}/{}",
+                self.default_artifact_root, experiment.experiment_id
+            );
+            let experiment: SqlExperiment = sqlx::query_as(
+                r#"
+                UPDATE experiments SET artifact_location = $1 WHERE name = $2
+                RETURNING *
+                "#,
+            )
+            .bind(&artifact_loc)
+            .bind(name)
+            .fetch_one(&mut tx)
+            .await?;
+            experiment
+        };
+
+        if let Some(tags) = tags.filter(|tags| !tags.is_empty()) {
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("INSERT INTO experiment_tags (experiment_id, key, value) ");
+
+            query_builder.push_values(tags, |mut b, tag| {
+                b.push_bind(experiment.experiment_id)
+                    .push_bind(&tag.key)
+                    .push_bind(&tag.value);
+            });
+
+            let query = query_builder.build();
+            query.execute(&mut tx).await?;
+        };
+        tx.commit().await?;
+
+        Ok(Experiment {
+            experiment_id: experiment.experiment_id.to_string(),
+            name: experiment.name,
+            artifact_location: experiment.artifact_location,
+            lifecycle_stage: experiment.lifecycle_stage,
+            tags: self.get_experiment_tags(experiment.experiment_id).await?,
+        })
+    }
+
+    async fn delete_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError> {
+        sqlx::query(
+            r#"UPDATE experiments SET lifecycle_stage = 'deleted' WHERE experiment_id = $1"#,
+        )
+        .bind(parse_experiment_id(experiment_id)?)
+        .execute(&self.connection)
+        .await?;
+        let experiment: SqlExperiment =
+            sqlx::query_as(r#"SELECT * FROM experiments WHERE experiment_id = $1"#)
+                .bind(parse_experiment_id(experiment_id)?)
+                .fetch_optional(&self.connection)
+                .await?
+                .ok_or_else(|| experiment_not_found(experiment_id))?;
+        Ok(Experiment {
+            experiment_id: experiment.experiment_id.to_string(),
+            name: experiment.name,
+            artifact_location: experiment.artifact_location,
+            lifecycle_stage: experiment.lifecycle_stage,
+            tags: self.get_experiment_tags(experiment.experiment_id).await?,
+        })
+    }
+
+    async fn restore_experiment(&self, experiment_id: &str) -> Result<Experiment, MlflowError> {
         let experiment: SqlExperiment = sqlx::query_as(
             r#"
-            UPDATE experiments SET name = $1
-            WHERE experiment_id = $2
+            UPDATE experiments SET lifecycle_stage = 'active'
+            WHERE experiment_id = $1
             RETURNING *
             "#,
         )
-        .bind(new_name)
-        .bind(experiment_id.parse::<i32>().unwrap())
+        .bind(parse_experiment_id(experiment_id)?)
         .fetch_one(&self.connection)
         .await?;
         Ok(Experiment {
@@ -483,13 +1061,87 @@ impl Store for SqliteStore {
             tags: self.get_experiment_tags(experiment.experiment_id).await?,
         })
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{initialize_database, SqliteStore};
-    use crate::{entities::ExperimentTag, stores::tracking::Store};
-    use uuid::Uuid;
+
+    async fn update_experiment(
+        &self,
+        experiment_id: &str,
+        new_name: &str,
+    ) -> Result<Experiment, MlflowError> {
+        let experiment: SqlExperiment = sqlx::query_as(
+            r#"
+            UPDATE experiments SET name = $1
+            WHERE experiment_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(new_name)
+        .bind(parse_experiment_id(experiment_id)?)
+        .fetch_one(&self.connection)
+        .await
+        .map_err(experiment_name_conflict(new_name))?;
+        Ok(Experiment {
+            experiment_id: experiment.experiment_id.to_string(),
+            name: experiment.name,
+            artifact_location: experiment.artifact_location,
+            lifecycle_stage: experiment.lifecycle_stage,
+            tags: self.get_experiment_tags(experiment.experiment_id).await?,
+        })
+    }
+
+    async fn set_experiment_tag(
+        &self,
+        experiment_id: &str,
+        tag: &ExperimentTag,
+    ) -> Result<(), MlflowError> {
+        let experiment = self.get_active_experiment(experiment_id).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO experiment_tags (key, value, experiment_id) VALUES ($1, $2, $3)
+            ON CONFLICT (key, experiment_id) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(&tag.key)
+        .bind(&tag.value)
+        .bind(experiment.experiment_id)
+        .execute(&self.connection)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_experiment_tag(
+        &self,
+        experiment_id: &str,
+        key: &str,
+    ) -> Result<(), MlflowError> {
+        let experiment = self.get_active_experiment(experiment_id).await?;
+        let result =
+            sqlx::query(r#"DELETE FROM experiment_tags WHERE experiment_id = $1 AND key = $2"#)
+                .bind(experiment.experiment_id)
+                .bind(key)
+                .execute(&self.connection)
+                .await?;
+        if result.rows_affected() == 0 {
+            return Err(MlflowError::ResourceDoesNotExist(format!(
+                "No tag with name: {} in experiment with id {}",
+                key, experiment_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_connection_pool, initialize_database, SqliteStore};
+    use crate::config::PoolConfig;
+    use crate::entities::{
+        Experiment, ExperimentTag, Metric, MetricWithRunId, Param, RunStatus, RunTag, ViewType,
+    };
+    use crate::env::MLFLOW_TRACKING_URI;
+    use crate::parser::common::Literal;
+    use crate::stores::tracking::error::MlflowError;
+    use crate::stores::tracking::{Store, SCHEMA_VERSION};
+    use uuid::Uuid;
 
     #[tokio::test]
     async fn test_get_experiment() {
@@ -505,251 +1157,1931 @@ mod tests {
         store.teardown().await;
     }
 
-    #[tokio::test]
-    async fn test_create_experiment() {
-        dotenv::from_filename(".env_dev").ok();
-        let store = SqliteStore::from_env().await.unwrap();
-        let name = Uuid::new_v4().to_string();
-        store.create_experiment(&name, None, None).await.unwrap();
-        let experiments = store.list_experiments().await.unwrap();
-        assert!(experiments
-            .into_iter()
-            .map(|e| e.name)
-            .collect::<Vec<String>>()
-            .contains(&name));
+    #[tokio::test]
+    async fn test_create_experiment() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        store.create_experiment(&name, None, None).await.unwrap();
+        let experiments = store.list_experiments(None, None).await.unwrap().items;
+        assert!(experiments
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<String>>()
+            .contains(&name));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_experiment_tags() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store
+            .create_experiment(
+                &name,
+                None,
+                Some(vec![&ExperimentTag {
+                    key: "key".to_string(),
+                    value: "value".to_string(),
+                }]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            experiment.tags,
+            vec![ExperimentTag {
+                key: "key".to_string(),
+                value: "value".to_string(),
+            }]
+        );
+
+        // An empty list of tags creates an experiment without tags
+        let experiment = store
+            .create_experiment(&Uuid::new_v4().to_string(), None, Some(vec![]))
+            .await
+            .unwrap();
+        assert!(experiment.tags.is_empty());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_experiments() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name1 = Uuid::new_v4().to_string();
+        let name2 = Uuid::new_v4().to_string();
+        store.create_experiment(&name1, None, None).await.unwrap();
+        store.create_experiment(&name2, None, None).await.unwrap();
+        let experiments = store.list_experiments(None, None).await.unwrap().items;
+        let experiment_names = experiments
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<String>>();
+        assert!(experiment_names.contains(&name1));
+        assert!(experiment_names.contains(&name2));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_experiments() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name1 = Uuid::new_v4().to_string();
+        let name2 = Uuid::new_v4().to_string();
+        store.create_experiment(&name1, None, None).await.unwrap();
+        store.create_experiment(&name2, None, None).await.unwrap();
+        let experiments = store
+            .search_experiments(Some(1), None, ViewType::ActiveOnly, None, None)
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        let experiments = store
+            .search_experiments(Some(2), None, ViewType::ActiveOnly, None, None)
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 2);
+        // Filter string
+        let filter_string = format!("name = '{}'", name1.as_str());
+        let experiments = store
+            .search_experiments(
+                None,
+                Some(filter_string.as_str()),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        let experiment = experiments.first().unwrap();
+        assert_eq!(experiment.name, name1);
+
+        let filter_string = format!("name IN ('{}', '{}')", name1, name2);
+        let experiments = store
+            .search_experiments(
+                None,
+                Some(filter_string.as_str()),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 2);
+
+        let filter_string = format!("name LIKE '{}%'", &name1[..6]);
+        let experiments = store
+            .search_experiments(
+                None,
+                Some(filter_string.as_str()),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        let experiment = experiments.first().unwrap();
+        assert_eq!(experiment.name, name1);
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_experiments_filter_by_tags() {
+        dotenv::from_filename(".env_dev").ok();
+        initialize_database().await.unwrap();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name1 = Uuid::new_v4().to_string();
+        let name2 = Uuid::new_v4().to_string();
+        let tags1 = vec![ExperimentTag {
+            key: "key".to_string(),
+            value: "value1".to_string(),
+        }];
+        let tags2 = vec![ExperimentTag {
+            key: "key".to_string(),
+            value: "value2".to_string(),
+        }];
+        store
+            .create_experiment(&name1, None, Some(tags1.iter().collect()))
+            .await
+            .unwrap();
+        store
+            .create_experiment(&name2, None, Some(tags2.iter().collect()))
+            .await
+            .unwrap();
+        let experiments = store
+            .search_experiments(
+                Some(1),
+                Some("tag.key = 'value1'"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        let experiment = experiments.first().unwrap();
+        assert_eq!(experiment.tags, tags1);
+
+        let experiments = store
+            .search_experiments(
+                Some(1),
+                Some("tag.key = 'value2'"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        let experiment = experiments.first().unwrap();
+        assert_eq!(experiment.tags, tags2);
+
+        let experiments = store
+            .search_experiments(
+                Some(1),
+                Some("tag.key LIKE 'val%' AND tag.key LIKE '%ue1'"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        let experiment = experiments.first().unwrap();
+        assert_eq!(experiment.tags, tags1);
+
+        let experiments = store
+            .search_experiments(
+                None,
+                Some("tag.key = 'value1' OR tag.key = 'value2'"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 2);
+
+        let experiments = store
+            .search_experiments(
+                None,
+                Some("NOT (tag.key = 'value1' OR name = 'Default')"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        assert_eq!(experiments[0].tags, tags2);
+
+        let experiments = store
+            .search_experiments(
+                None,
+                Some("tag.key IS NULL"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 1);
+        assert_eq!(experiments[0].name, "Default");
+
+        let experiments = store
+            .search_experiments(
+                Some(1),
+                Some("tag.nonexistent_key LIKE 'val%'"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert!(experiments.is_empty());
+
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_experiments_order_by() {
+        dotenv::from_filename(".env_dev").ok();
+        initialize_database().await.unwrap();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name1 = "a";
+        let name2 = "b";
+        store.create_experiment(name1, None, None).await.unwrap();
+        store.create_experiment(name2, None, None).await.unwrap();
+        let experiments = store
+            .search_experiments(
+                None,
+                Some("name != 'Default'"),
+                ViewType::ActiveOnly,
+                Some(vec!["name"]),
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 2);
+        assert_eq!(experiments[0].name, "a");
+        assert_eq!(experiments[1].name, "b");
+
+        let experiments = store
+            .search_experiments(
+                None,
+                Some("name != 'Default'"),
+                ViewType::ActiveOnly,
+                Some(vec!["name DESC"]),
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(experiments.len(), 2);
+        assert_eq!(experiments[0].name, "b");
+        assert_eq!(experiments[1].name, "a");
+
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_experiments_filter_string_containing_invalid_entities() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let filter_string = "param.key = 'value'".to_string();
+        let res = store
+            .search_experiments(
+                None,
+                Some(filter_string.as_str()),
+                ViewType::ActiveOnly,
+                None,
+                None,
+            )
+            .await;
+        assert!(res.is_err());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_experiments_injection() {
+        async fn search(
+            store: &SqliteStore,
+            filter: &str,
+            order_by: Option<Vec<&str>>,
+        ) -> Result<Vec<Experiment>, MlflowError> {
+            Ok(store
+                .search_experiments(None, Some(filter), ViewType::All, order_by, None)
+                .await?
+                .items)
+        }
+
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = format!("{}' OR '1'='1", Uuid::new_v4());
+        let tags = [ExperimentTag {
+            key: "k') OR 1=1 --".to_string(),
+            value: "v".to_string(),
+        }];
+        store
+            .create_experiment(&name, None, Some(tags.iter().collect()))
+            .await
+            .unwrap();
+        // Keys and values are bound, so they are matched literally
+        let experiments = search(&store, "name = 'x OR 1=1 --'", None).await.unwrap();
+        assert!(experiments.is_empty());
+        let experiments = search(&store, &format!("name LIKE '{}%'", &name[..36]), None)
+            .await
+            .unwrap();
+        assert_eq!(experiments.len(), 1);
+        for filter in [
+            format!("name = {}", Literal::String(name.clone())),
+            format!("name = \"{}\"", name),
+        ] {
+            let experiments = search(&store, &filter, None).await.unwrap();
+            assert_eq!(experiments.len(), 1, "filter: {}", filter);
+            assert_eq!(experiments[0].name, name);
+        }
+        let experiments = search(&store, "tag.`k') OR 1=1 --` = 'v'", None)
+            .await
+            .unwrap();
+        assert_eq!(experiments.len(), 1);
+        assert_eq!(experiments[0].name, name);
+
+        // Attribute keys must be columns of the experiments table
+        for filter in [
+            "attribute.`name = name OR 1` = 'a'",
+            "attribute.`1=1; DROP TABLE experiments; --` = 'a'",
+            "attribute.creation_time > 0",
+        ] {
+            assert!(matches!(
+                search(&store, filter, None).await,
+                Err(MlflowError::InvalidParameter(_))
+            ));
+        }
+        for order_by in [
+            "name; DROP TABLE experiments",
+            "`name; DROP TABLE experiments`",
+            "`(SELECT 1)` DESC",
+        ] {
+            assert!(matches!(
+                search(&store, "", Some(vec![order_by])).await,
+                Err(MlflowError::InvalidParameter(_))
+            ));
+        }
+        assert!(matches!(
+            search(&store, "name LIKE 1", None).await,
+            Err(MlflowError::InvalidParameter(_))
+        ));
+
+        // The tables are intact
+        assert!(store
+            .list_experiments(None, None)
+            .await
+            .unwrap()
+            .items
+            .iter()
+            .any(|e| e.name == name));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_experiment() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let deleted_experiment = store
+            .delete_experiment(&experiment.experiment_id)
+            .await
+            .unwrap();
+        assert_eq!(deleted_experiment.lifecycle_stage, "deleted");
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_experiment() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let deleted_experiment = store
+            .delete_experiment(&experiment.experiment_id)
+            .await
+            .unwrap();
+        let restored_experiment = store
+            .restore_experiment(&deleted_experiment.experiment_id)
+            .await
+            .unwrap();
+        assert_eq!(restored_experiment.lifecycle_stage, "active");
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_experiment() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let new_name = Uuid::new_v4().to_string();
+        let updated_experiment = store
+            .update_experiment(&experiment.experiment_id, &new_name)
+            .await
+            .unwrap();
+        assert_eq!(updated_experiment.name, new_name);
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_runs() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        store.create_run("0", None, None, None, None).await.unwrap();
+        let runs = store
+            .search_runs(vec!["0"], None, ViewType::ActiveOnly, None, None, None)
+            .await
+            .unwrap()
+            .items;
+        assert!(!runs.is_empty());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_run() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let run = store
+            .create_run(
+                &experiment.experiment_id,
+                Some("user"),
+                Some(1),
+                Some("run"),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.info.experiment_id, experiment.experiment_id);
+        assert_eq!(run.info.name, "run");
+        assert_eq!(run.info.user_id, "user");
+        assert_eq!(run.info.status, "RUNNING");
+        assert_eq!(run.info.start_time, 1);
+        assert_eq!(run.info.end_time, None);
+        assert_eq!(run.info.lifecycle_stage, "active");
+        assert_eq!(
+            run.info.artifact_uri,
+            format!(
+                "{}/{}/artifacts",
+                experiment.artifact_location, run.info.run_id
+            )
+        );
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_run_with_run_name_tag() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let tag = RunTag {
+            key: "mlflow.runName".to_string(),
+            value: "run".to_string(),
+        };
+        let run = store
+            .create_run("0", None, None, Some("run"), Some(vec![&tag]))
+            .await
+            .unwrap();
+        assert_eq!(run.info.name, "run");
+        let res = store
+            .create_run("0", None, None, Some("other"), Some(vec![&tag]))
+            .await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_run_in_deleted_experiment() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        store
+            .delete_experiment(&experiment.experiment_id)
+            .await
+            .unwrap();
+        let res = store
+            .create_run(&experiment.experiment_id, None, None, None, None)
+            .await;
+        assert!(res.is_err());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_run() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_info = store
+            .update_run(
+                &run.info.run_id,
+                Some(RunStatus::Finished),
+                Some(100),
+                Some("new_name"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(run_info.status, "FINISHED");
+        assert_eq!(run_info.end_time, Some(100));
+        assert_eq!(run_info.name, "new_name");
+
+        let run_info = store
+            .update_run(&run.info.run_id, Some(RunStatus::Failed), None, None)
+            .await
+            .unwrap();
+        assert_eq!(run_info.status, "FAILED");
+        assert_eq!(run_info.end_time, Some(100));
+        assert_eq!(run_info.name, "new_name");
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_run() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let deleted_run = store.delete_run(&run.info.run_id).await.unwrap();
+        assert_eq!(deleted_run.info.lifecycle_stage, "deleted");
+        let (deleted_time,): (Option<i64>,) =
+            sqlx::query_as(r#"SELECT deleted_time FROM runs WHERE run_uuid = $1"#)
+                .bind(&run.info.run_id)
+                .fetch_one(&store.connection)
+                .await
+                .unwrap();
+        assert!(deleted_time.is_some());
+        let res = store
+            .update_run(&run.info.run_id, Some(RunStatus::Finished), None, None)
+            .await;
+        assert!(res.is_err());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_run() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let deleted_run = store.delete_run(&run.info.run_id).await.unwrap();
+        let restored_run = store.restore_run(&deleted_run.info.run_id).await.unwrap();
+        assert_eq!(restored_run.info.lifecycle_stage, "active");
+        let (deleted_time,): (Option<i64>,) =
+            sqlx::query_as(r#"SELECT deleted_time FROM runs WHERE run_uuid = $1"#)
+                .bind(&run.info.run_id)
+                .fetch_one(&store.connection)
+                .await
+                .unwrap();
+        assert_eq!(deleted_time, None);
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_log_metric() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_id = run.info.run_id.as_str();
+        for (value, timestamp, step) in [(1.0, 1, 0), (2.0, 2, 2), (3.0, 3, 1)] {
+            store
+                .log_metric(
+                    run_id,
+                    &Metric {
+                        key: "m".to_string(),
+                        value,
+                        timestamp,
+                        step,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        let history: Vec<(f64,)> =
+            sqlx::query_as(r#"SELECT value FROM metrics WHERE run_uuid = $1 ORDER BY value"#)
+                .bind(run_id)
+                .fetch_all(&store.connection)
+                .await
+                .unwrap();
+        assert_eq!(history, vec![(1.0,), (2.0,), (3.0,)]);
+        // The metric with the largest step is the latest one
+        let latest: (f64, i64, i64) = sqlx::query_as(
+            r#"SELECT value, timestamp, step FROM latest_metrics WHERE run_uuid = $1 AND key = 'm'"#,
+        )
+        .bind(run_id)
+        .fetch_one(&store.connection)
+        .await
+        .unwrap();
+        assert_eq!(latest, (2.0, 2, 2));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_log_param() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_id = run.info.run_id.as_str();
+        let param = Param {
+            key: "p".to_string(),
+            value: "a".to_string(),
+        };
+        store.log_param(run_id, &param).await.unwrap();
+        // Logging the same value again is a no-op
+        store.log_param(run_id, &param).await.unwrap();
+        let res = store
+            .log_param(
+                run_id,
+                &Param {
+                    key: "p".to_string(),
+                    value: "b".to_string(),
+                },
+            )
+            .await;
+        assert!(res.is_err());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_set_and_delete_tag() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_id = run.info.run_id.as_str();
+        for value in ["a", "b"] {
+            store
+                .set_tag(
+                    run_id,
+                    &RunTag {
+                        key: "t".to_string(),
+                        value: value.to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        let tags: Vec<(String,)> =
+            sqlx::query_as(r#"SELECT value FROM tags WHERE run_uuid = $1 AND key = 't'"#)
+                .bind(run_id)
+                .fetch_all(&store.connection)
+                .await
+                .unwrap();
+        assert_eq!(tags, vec![("b".to_string(),)]);
+        store.delete_tag(run_id, "t").await.unwrap();
+        assert!(matches!(
+            store.delete_tag(run_id, "t").await,
+            Err(MlflowError::ResourceDoesNotExist(_))
+        ));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_log_to_deleted_run() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        store.delete_run(&run.info.run_id).await.unwrap();
+        let res = store
+            .log_param(
+                &run.info.run_id,
+                &Param {
+                    key: "p".to_string(),
+                    value: "a".to_string(),
+                },
+            )
+            .await;
+        assert!(res.is_err());
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_log_batch() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_id = run.info.run_id.as_str();
+        let metrics = [(1.0, 1, 0), (2.0, 2, 2), (3.0, 3, 1)]
+            .into_iter()
+            .map(|(value, timestamp, step)| Metric {
+                key: "m".to_string(),
+                value,
+                timestamp,
+                step,
+            })
+            .collect::<Vec<_>>();
+        let params = [
+            Param {
+                key: "p1".to_string(),
+                value: "a".to_string(),
+            },
+            Param {
+                key: "p2".to_string(),
+                value: "b".to_string(),
+            },
+        ];
+        let tags = [
+            RunTag {
+                key: "t".to_string(),
+                value: "a".to_string(),
+            },
+            RunTag {
+                key: "t".to_string(),
+                value: "b".to_string(),
+            },
+        ];
+        store
+            .log_batch(
+                run_id,
+                metrics.iter().collect(),
+                params.iter().collect(),
+                tags.iter().collect(),
+            )
+            .await
+            .unwrap();
+        // Logging the same batch again is allowed since the param values don't change
+        store
+            .log_batch(
+                run_id,
+                metrics.iter().collect(),
+                params.iter().collect(),
+                tags.iter().collect(),
+            )
+            .await
+            .unwrap();
+
+        let history: Vec<(f64,)> =
+            sqlx::query_as(r#"SELECT value FROM metrics WHERE run_uuid = $1 ORDER BY value"#)
+                .bind(run_id)
+                .fetch_all(&store.connection)
+                .await
+                .unwrap();
+        assert_eq!(history, vec![(1.0,), (2.0,), (3.0,)]);
+        let latest: (f64, i64, i64) = sqlx::query_as(
+            r#"SELECT value, timestamp, step FROM latest_metrics WHERE run_uuid = $1 AND key = 'm'"#,
+        )
+        .bind(run_id)
+        .fetch_one(&store.connection)
+        .await
+        .unwrap();
+        assert_eq!(latest, (2.0, 2, 2));
+        let logged_params: Vec<(String, String)> =
+            sqlx::query_as(r#"SELECT key, value FROM params WHERE run_uuid = $1 ORDER BY key"#)
+                .bind(run_id)
+                .fetch_all(&store.connection)
+                .await
+                .unwrap();
+        assert_eq!(
+            logged_params,
+            vec![
+                ("p1".to_string(), "a".to_string()),
+                ("p2".to_string(), "b".to_string())
+            ]
+        );
+        let logged_tags: Vec<(String, String)> =
+            sqlx::query_as(r#"SELECT key, value FROM tags WHERE run_uuid = $1"#)
+                .bind(run_id)
+                .fetch_all(&store.connection)
+                .await
+                .unwrap();
+        assert_eq!(logged_tags, vec![("t".to_string(), "b".to_string())]);
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_log_batch_is_transactional() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_id = run.info.run_id.as_str();
+        let param = Param {
+            key: "p".to_string(),
+            value: "a".to_string(),
+        };
+        store.log_param(run_id, &param).await.unwrap();
+        let metric = Metric {
+            key: "m".to_string(),
+            value: 1.0,
+            timestamp: 1,
+            step: 0,
+        };
+        let changed_param = Param {
+            key: "p".to_string(),
+            value: "b".to_string(),
+        };
+        let res = store
+            .log_batch(run_id, vec![&metric], vec![&changed_param], vec![])
+            .await;
+        assert!(res.is_err());
+        let count: (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM metrics WHERE run_uuid = $1"#)
+            .bind(run_id)
+            .fetch_one(&store.connection)
+            .await
+            .unwrap();
+        assert_eq!(count, (0,));
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_log_batch_limits() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let run = store.create_run("0", None, None, None, None).await.unwrap();
+        let run_id = run.info.run_id.as_str();
+        let metrics = (0..1001)
+            .map(|i| Metric {
+                key: "m".to_string(),
+                value: 0.0,
+                timestamp: 0,
+                step: i,
+            })
+            .collect::<Vec<_>>();
+        let params = (0..101)
+            .map(|i| Param {
+                key: format!("p{}", i),
+                value: "a".to_string(),
+            })
+            .collect::<Vec<_>>();
+        let tags = (0..101)
+            .map(|i| RunTag {
+                key: format!("t{}", i),
+                value: "a".to_string(),
+            })
+            .collect::<Vec<_>>();
+        let res = store
+            .log_batch(run_id, metrics.iter().collect(), vec![], vec![])
+            .await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        let res = store
+            .log_batch(run_id, vec![], params.iter().collect(), vec![])
+            .await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        let res = store
+            .log_batch(run_id, vec![], vec![], tags.iter().collect())
+            .await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        let res = store
+            .log_batch(
+                run_id,
+                metrics[..950].iter().collect(),
+                params[..100].iter().collect(),
+                vec![],
+            )
+            .await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        // Exactly at the limit is fine
+        store
+            .log_batch(run_id, metrics[..1000].iter().collect(), vec![], vec![])
+            .await
+            .unwrap();
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_run_data() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let tag = RunTag {
+            key: "t".to_string(),
+            value: "a".to_string(),
+        };
+        let run = store
+            .create_run("0", None, None, None, Some(vec![&tag]))
+            .await
+            .unwrap();
+        assert_eq!(run.data.tags, vec![tag]);
+        let run_id = run.info.run_id.as_str();
+        let metrics = [(1.0, 1, 0), (2.0, 2, 1)]
+            .into_iter()
+            .map(|(value, timestamp, step)| Metric {
+                key: "m".to_string(),
+                value,
+                timestamp,
+                step,
+            })
+            .collect::<Vec<_>>();
+        let param = Param {
+            key: "p".to_string(),
+            value: "a".to_string(),
+        };
+        store
+            .log_batch(run_id, metrics.iter().collect(), vec![&param], vec![])
+            .await
+            .unwrap();
+        let run = store.get_run(run_id).await.unwrap();
+        assert_eq!(run.data.params, vec![param]);
+        // Only the latest value of each metric is returned
+        assert_eq!(
+            run.data.metrics,
+            vec![Metric {
+                key: "m".to_string(),
+                value: 2.0,
+                timestamp: 2,
+                step: 1,
+            }]
+        );
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_data() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let experiment_id = experiment.experiment_id.as_str();
+        let mut run_ids = vec![];
+        for i in 0..3 {
+            let run = store
+                .create_run(experiment_id, None, None, None, None)
+                .await
+                .unwrap();
+            let param = Param {
+                key: "p".to_string(),
+                value: i.to_string(),
+            };
+            store.log_param(&run.info.run_id, &param).await.unwrap();
+            run_ids.push(run.info.run_id);
+        }
+        let runs = store
+            .search_runs(
+                vec![experiment_id],
+                None,
+                ViewType::ActiveOnly,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(runs.len(), 3);
+        for run in runs {
+            let i = run_ids
+                .iter()
+                .position(|id| id == &run.info.run_id)
+                .unwrap();
+            assert_eq!(
+                run.data.params,
+                vec![Param {
+                    key: "p".to_string(),
+                    value: i.to_string(),
+                }]
+            );
+            assert!(run.data.metrics.is_empty());
+            assert!(run.data.tags.is_empty());
+        }
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_filter() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let experiment_id = experiment.experiment_id.as_str();
+        for (i, (run_name, param, metric, tag)) in [
+            ("a", "x", 0.1, Some("foo")),
+            ("b", "y", 0.5, Some("bar")),
+            ("c", "x", 0.9, None),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let run = store
+                .create_run(
+                    experiment_id,
+                    None,
+                    Some(i as i64 + 1),
+                    Some(run_name),
+                    None,
+                )
+                .await
+                .unwrap();
+            let metric = Metric {
+                key: "m".to_string(),
+                value: metric,
+                timestamp: 0,
+                step: 0,
+            };
+            let param = Param {
+                key: "p".to_string(),
+                value: param.to_string(),
+            };
+            let tags = tag
+                .map(|value| RunTag {
+                    key: "t".to_string(),
+                    value: value.to_string(),
+                })
+                .into_iter()
+                .collect::<Vec<_>>();
+            store
+                .log_batch(
+                    &run.info.run_id,
+                    vec![&metric],
+                    vec![&param],
+                    tags.iter().collect(),
+                )
+                .await
+                .unwrap();
+        }
+
+        for (filter, expected) in [
+            ("", vec!["a", "b", "c"]),
+            ("params.p = 'x'", vec!["a", "c"]),
+            ("params.p != 'x'", vec!["b"]),
+            ("metrics.m > 0.3", vec!["b", "c"]),
+            ("metrics.m <= 0.5", vec!["a", "b"]),
+            ("metrics.m > 1", vec![]),
+            ("metrics.m >= 0.5 AND params.p = 'x'", vec!["c"]),
+            ("tags.t LIKE 'f%'", vec!["a"]),
+            ("tags.t ILIKE 'F%'", vec!["a"]),
+            ("tags.t != 'foo'", vec!["b"]),
+            ("attributes.run_name = 'b'", vec!["b"]),
+            ("attributes.start_time > 1", vec!["b", "c"]),
+            ("attributes.status = 'RUNNING'", vec!["a", "b", "c"]),
+            ("params.missing = 'x'", vec![]),
+            ("tags.t = 'foo' OR tags.t = 'bar'", vec!["a", "b"]),
+            (
+                "attributes.run_name = 'a' OR attributes.run_name = 'c'",
+                vec!["a", "c"],
+            ),
+            (
+                "params.p = 'y' OR metrics.m > 0.8 AND params.p = 'x'",
+                vec!["b", "c"],
+            ),
+            (
+                "(params.p = 'y' OR metrics.m > 0.8) AND tags.t = 'bar'",
+                vec!["b"],
+            ),
+            ("NOT tags.t = 'foo'", vec!["b", "c"]),
+            ("NOT (params.p = 'x' OR attributes.run_name = 'b')", vec![]),
+            ("attributes.run_name IN ('a', 'c')", vec!["a", "c"]),
+            ("params.p NOT IN ('x')", vec!["b"]),
+            ("tags.t IN ('foo', 'bar', 'baz')", vec!["a", "b"]),
+            ("metrics.m IN (0.1, 0.9)", vec!["a", "c"]),
+            ("attributes.start_time NOT IN (1, 2)", vec!["c"]),
+            ("tags.t IS NULL", vec!["c"]),
+            ("tags.t IS NOT NULL", vec!["a", "b"]),
+            ("NOT tags.t IS NULL", vec!["a", "b"]),
+            ("metrics.missing IS NULL", vec!["a", "b", "c"]),
+            ("metrics.m IS NULL", vec![]),
+            (
+                "params.p IS NOT NULL AND attributes.end_time IS NULL",
+                vec!["a", "b", "c"],
+            ),
+        ] {
+            let runs = store
+                .search_runs(
+                    vec![experiment_id],
+                    Some(filter),
+                    ViewType::ActiveOnly,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap()
+                .items;
+            let mut names = runs.into_iter().map(|r| r.info.name).collect::<Vec<_>>();
+            names.sort();
+            assert_eq!(names, expected, "filter: {}", filter);
+        }
+
+        for filter in [
+            "metrics.m = 'a'",
+            "metrics.m LIKE 0.5",
+            "params.p > 1",
+            "attributes.unknown = 'a'",
+            "attributes.start_time = 'a'",
+            "params.p = 'x' OR 1 = 1",
+            "params.p = 'x' OR",
+            "(params.p = 'x'",
+            "NOT metrics.m = 'a'",
+            "params.p IN 'x'",
+            "params.p = ('x')",
+            "params.p IN (1, 2)",
+            "metrics.m IN ('a')",
+            "params.p IN ()",
+        ] {
+            let res = store
+                .search_runs(
+                    vec![experiment_id],
+                    Some(filter),
+                    ViewType::ActiveOnly,
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+            assert!(
+                matches!(res, Err(MlflowError::InvalidParameter(_))),
+                "filter: {}",
+                filter
+            );
+        }
+
+        let runs = store
+            .search_runs(
+                vec![experiment_id],
+                Some("attributes.run_name != 'b'"),
+                ViewType::ActiveOnly,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        let filter = format!(
+            "attributes.run_id IN ({})",
+            runs.iter()
+                .map(|r| format!("'{}'", r.info.run_id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let runs = store
+            .search_runs(
+                vec![experiment_id],
+                Some(&filter),
+                ViewType::ActiveOnly,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .items;
+        let mut names = runs.into_iter().map(|r| r.info.name).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["a", "c"]);
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_numeric_literals() {
+        dotenv::from_filename(".env_dev").ok();
+        // A single connection, so that every search reuses the statements cached by the others
+        let store = SqliteStore::new(
+            &std::env::var(MLFLOW_TRACKING_URI).unwrap(),
+            "./artifacts",
+            &PoolConfig {
+                max_connections: 1,
+                ..PoolConfig::default()
+            },
+        )
+        .await
+        .unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let experiment_id = experiment.experiment_id.as_str();
+        let run = store
+            .create_run(experiment_id, None, Some(2), None, None)
+            .await
+            .unwrap();
+        store
+            .log_metric(
+                &run.info.run_id,
+                &Metric {
+                    key: "m".to_string(),
+                    value: 0.5,
+                    timestamp: 0,
+                    step: 0,
+                },
+            )
+            .await
+            .unwrap();
+
+        // Integers and floats are compared to the same columns in turn
+        for (filter, expected) in [
+            ("metrics.m > 0.25", 1),
+            ("metrics.m > 1", 0),
+            ("metrics.m > 0.75", 0),
+            ("metrics.m IN (1, 0.5)", 1),
+            ("metrics.m IN (0.5, 1)", 1),
+            ("attributes.start_time > 1", 1),
+            ("attributes.start_time > 2.5", 0),
+            ("attributes.start_time > 1", 1),
+        ] {
+            let runs = store
+                .search_runs(
+                    vec![experiment_id],
+                    Some(filter),
+                    ViewType::ActiveOnly,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap()
+                .items;
+            assert_eq!(runs.len(), expected, "filter: {}", filter);
+        }
+        store.teardown().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_order_by() {
+        dotenv::from_filename(".env_dev").ok();
+        let store = SqliteStore::from_env().await.unwrap();
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let experiment_id = experiment.experiment_id.as_str();
+        for (start_time, run_name, metric, param) in [
+            (1, "a", Some(0.5), Some("y")),
+            (2, "b", Some(0.1), None),
+            (3, "c", None, Some("x")),
+            (4, "d", Some(f64::NAN), Some("x")),
+            (5, "e", Some(0.5), Some("z")),
+        ] {
+            let run = store
+                .create_run(experiment_id, None, Some(start_time), Some(run_name), None)
+                .await
+                .unwrap();
+            let metrics = metric
+                .map(|value| Metric {
+                    key: "m".to_string(),
+                    value,
+                    timestamp: 0,
+                    step: 0,
+                })
+                .into_iter()
+                .collect::<Vec<_>>();
+            let params = param
+                .map(|value| Param {
+                    key: "p".to_string(),
+                    value: value.to_string(),
+                })
+                .into_iter()
+                .collect::<Vec<_>>();
+            store
+                .log_batch(
+                    &run.info.run_id,
+                    metrics.iter().collect(),
+                    params.iter().collect(),
+                    vec![],
+                )
+                .await
+                .unwrap();
+        }
+
+        for (order_by, expected) in [
+            (vec![], vec!["e", "d", "c", "b", "a"]),
+            (vec!["attributes.start_time"], vec!["a", "b", "c", "d", "e"]),
+            (vec!["run_name DESC"], vec!["e", "d", "c", "b", "a"]),
+            (vec!["metrics.m ASC"], vec!["b", "e", "a", "d", "c"]),
+            (vec!["metrics.m DESC"], vec!["e", "a", "b", "d", "c"]),
+            (vec!["params.p"], vec!["d", "c", "a", "e", "b"]),
+            (
+                vec!["params.p DESC", "start_time"],
+                vec!["e", "a", "c", "d", "b"],
+            ),
+            (
+                vec!["tags.`mlflow.runName` DESC"],
+                vec!["e", "d", "c", "b", "a"],
+            ),
+            (vec!["tags.missing"], vec!["e", "d", "c", "b", "a"]),
+        ] {
+            let runs = store
+                .search_runs(
+                    vec![experiment_id],
+                    None,
+                    ViewType::ActiveOnly,
+                    Some(order_by.clone()),
+                    None,
+                    None,
+                )
+                .await
+                .unwrap()
+                .items;
+            let names = runs.into_iter().map(|r| r.info.name).collect::<Vec<_>>();
+            assert_eq!(names, expected, "order_by: {:?}", order_by);
+        }
+
+        for order_by in ["attributes.unknown", "metrics.m UP"] {
+            let res = store
+                .search_runs(
+                    vec![experiment_id],
+                    None,
+                    ViewType::ActiveOnly,
+                    Some(vec![order_by]),
+                    None,
+                    None,
+                )
+                .await;
+            assert!(
+                matches!(res, Err(MlflowError::InvalidParameter(_))),
+                "order_by: {}",
+                order_by
+            );
+        }
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_create_experiment_tags() {
+    async fn test_search_runs_pagination() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
         let name = Uuid::new_v4().to_string();
-        let experiment = store
-            .create_experiment(
-                &name,
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let experiment_id = experiment.experiment_id.as_str();
+        for start_time in 1..=5 {
+            store
+                .create_run(experiment_id, None, Some(start_time), None, None)
+                .await
+                .unwrap();
+        }
+
+        let mut start_times = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let page = store
+                .search_runs(
+                    vec![experiment_id],
+                    None,
+                    ViewType::ActiveOnly,
+                    Some(vec!["start_time"]),
+                    Some(2),
+                    page_token.as_deref(),
+                )
+                .await
+                .unwrap();
+            assert!(page.items.len() <= 2);
+            start_times.extend(page.items.iter().map(|r| r.info.start_time));
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+        assert_eq!(start_times, vec![1, 2, 3, 4, 5]);
+
+        let page = store
+            .search_runs(
+                vec![experiment_id],
+                None,
+                ViewType::ActiveOnly,
+                None,
+                Some(5),
                 None,
-                Some(vec![&ExperimentTag {
-                    key: "key".to_string(),
-                    value: "value".to_string(),
-                }]),
             )
             .await
             .unwrap();
-        assert_eq!(
-            experiment.tags,
-            vec![ExperimentTag {
-                key: "key".to_string(),
-                value: "value".to_string(),
-            }]
-        );
+        assert_eq!(page.items.len(), 5);
+        assert!(page.next_page_token.is_none());
+
+        for (max_results, page_token) in [
+            (Some(0), None),
+            (Some(50001), None),
+            (None, Some("not a token")),
+            (None, Some("eyJmb28iOiAxfQ==")),
+            (None, Some("eyJvZmZzZXQiOiAtMX0=")),
+        ] {
+            let res = store
+                .search_runs(
+                    vec![experiment_id],
+                    None,
+                    ViewType::ActiveOnly,
+                    None,
+                    max_results,
+                    page_token,
+                )
+                .await;
+            assert!(
+                matches!(res, Err(MlflowError::InvalidParameter(_))),
+                "max_results: {:?}, page_token: {:?}",
+                max_results,
+                page_token
+            );
+        }
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_list_experiments() {
+    async fn test_list_experiments_pagination() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
-        let name1 = Uuid::new_v4().to_string();
-        let name2 = Uuid::new_v4().to_string();
-        store.create_experiment(&name1, None, None).await.unwrap();
-        store.create_experiment(&name2, None, None).await.unwrap();
-        let experiments = store.list_experiments().await.unwrap();
-        let experiment_names = experiments
-            .into_iter()
-            .map(|e| e.name)
-            .collect::<Vec<String>>();
-        assert!(experiment_names.contains(&name1));
-        assert!(experiment_names.contains(&name2));
+        for _ in 0..3 {
+            let name = Uuid::new_v4().to_string();
+            store.create_experiment(&name, None, None).await.unwrap();
+        }
+        let all = store.list_experiments(None, None).await.unwrap();
+        assert!(all.next_page_token.is_none());
+
+        let mut experiment_ids = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let page = store
+                .list_experiments(Some(2), page_token.as_deref())
+                .await
+                .unwrap();
+            experiment_ids.extend(page.items.into_iter().map(|e| e.experiment_id));
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+        assert_eq!(
+            experiment_ids,
+            all.items
+                .into_iter()
+                .map(|e| e.experiment_id)
+                .collect::<Vec<_>>()
+        );
+
+        let res = store.list_experiments(None, Some("%%%")).await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_search_experiments() {
+    async fn test_search_view_type() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
-        let name1 = Uuid::new_v4().to_string();
-        let name2 = Uuid::new_v4().to_string();
-        store.create_experiment(&name1, None, None).await.unwrap();
-        store.create_experiment(&name2, None, None).await.unwrap();
-        let experiments = store.search_experiments(Some(1), None, None).await.unwrap();
-        assert_eq!(experiments.len(), 1);
-        let experiments = store.search_experiments(Some(2), None, None).await.unwrap();
-        assert_eq!(experiments.len(), 2);
-        // Filter string
-        let filter_string = format!("name = '{}'", name1.as_str());
-        let experiments = store
-            .search_experiments(None, Some(filter_string.as_str()), None)
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let experiment_id = experiment.experiment_id.as_str();
+        let active_run = store
+            .create_run(experiment_id, None, None, None, None)
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 1);
-        let experiment = experiments.first().unwrap();
-        assert_eq!(experiment.name, name1);
-
-        let filter_string = format!("name LIKE '{}%'", &name1[..6]);
-        let experiments = store
-            .search_experiments(None, Some(filter_string.as_str()), None)
+        let deleted_run = store
+            .create_run(experiment_id, None, None, None, None)
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 1);
-        let experiment = experiments.first().unwrap();
-        assert_eq!(experiment.name, name1);
+        store.delete_run(&deleted_run.info.run_id).await.unwrap();
+
+        for (view_type, expected) in [
+            (ViewType::ActiveOnly, vec![&active_run.info.run_id]),
+            (ViewType::DeletedOnly, vec![&deleted_run.info.run_id]),
+            (
+                ViewType::All,
+                vec![&active_run.info.run_id, &deleted_run.info.run_id],
+            ),
+        ] {
+            let runs = store
+                .search_runs(vec![experiment_id], None, view_type, None, None, None)
+                .await
+                .unwrap()
+                .items;
+            let mut run_ids = runs.iter().map(|r| &r.info.run_id).collect::<Vec<_>>();
+            run_ids.sort();
+            let mut expected = expected;
+            expected.sort();
+            assert_eq!(run_ids, expected, "view_type: {:?}", view_type);
+        }
+
+        store.delete_experiment(experiment_id).await.unwrap();
+        for (view_type, is_found) in [
+            (ViewType::ActiveOnly, false),
+            (ViewType::DeletedOnly, true),
+            (ViewType::All, true),
+        ] {
+            let experiments = store
+                .search_experiments(Some(50000), None, view_type, None, None)
+                .await
+                .unwrap()
+                .items;
+            assert_eq!(
+                experiments.iter().any(|e| e.experiment_id == experiment_id),
+                is_found,
+                "view_type: {:?}",
+                view_type
+            );
+        }
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_search_experiments_filter_by_tags() {
+    async fn test_get_metric_history() {
         dotenv::from_filename(".env_dev").ok();
-        initialize_database().await.unwrap();
         let store = SqliteStore::from_env().await.unwrap();
-        let name1 = Uuid::new_v4().to_string();
-        let name2 = Uuid::new_v4().to_string();
-        let tags1 = vec![ExperimentTag {
-            key: "key".to_string(),
-            value: "value1".to_string(),
-        }];
-        let tags2 = vec![ExperimentTag {
-            key: "key".to_string(),
-            value: "value2".to_string(),
-        }];
-        store
-            .create_experiment(&name1, None, Some(tags1.iter().collect()))
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let run = store
+            .create_run(&experiment.experiment_id, None, None, None, None)
             .await
             .unwrap();
+        let run_id = run.info.run_id.as_str();
+        let metrics = [(2, 1, 0.3), (0, 2, 0.1), (1, 0, f64::NAN), (0, 1, 0.2)]
+            .into_iter()
+            .map(|(step, timestamp, value)| Metric {
+                key: "m".to_string(),
+                value,
+                timestamp,
+                step,
+            })
+            .collect::<Vec<_>>();
         store
-            .create_experiment(&name2, None, Some(tags2.iter().collect()))
+            .log_batch(run_id, metrics.iter().collect(), vec![], vec![])
             .await
             .unwrap();
-        let experiments = store
-            .search_experiments(Some(1), Some("tag.key = 'value1'"), None)
+        let other = Metric {
+            key: "other".to_string(),
+            value: 1.0,
+            timestamp: 0,
+            step: 0,
+        };
+        store.log_metric(run_id, &other).await.unwrap();
+
+        let history = store
+            .get_metric_history(run_id, "m", None, None)
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 1);
-        let experiment = experiments.first().unwrap();
-        assert_eq!(experiment.tags, tags1);
+        assert!(history.next_page_token.is_none());
+        assert_eq!(
+            history
+                .items
+                .iter()
+                .map(|m| (m.step, m.timestamp))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (0, 2), (1, 0), (2, 1)]
+        );
+        assert_eq!(history.items[0].value, 0.2);
+        assert!(history.items[2].value.is_nan());
 
-        let experiments = store
-            .search_experiments(Some(1), Some("tag.key = 'value2'"), None)
+        let first_page = store
+            .get_metric_history(run_id, "m", Some(3), None)
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 1);
-        let experiment = experiments.first().unwrap();
-        assert_eq!(experiment.tags, tags2);
-
-        let experiments = store
-            .search_experiments(
-                Some(1),
-                Some("tag.key LIKE 'val%' AND tag.key LIKE '%ue1'"),
-                None,
-            )
+        assert_eq!(first_page.items.len(), 3);
+        let second_page = store
+            .get_metric_history(run_id, "m", Some(3), first_page.next_page_token.as_deref())
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 1);
-        let experiment = experiments.first().unwrap();
-        assert_eq!(experiment.tags, tags1);
+        assert_eq!(second_page.items.len(), 1);
+        assert_eq!(second_page.items[0].step, 2);
+        assert!(second_page.next_page_token.is_none());
 
-        let experiments = store
-            .search_experiments(Some(1), Some("tag.nonexistent_key LIKE 'val%'"), None)
+        let history = store
+            .get_metric_history(run_id, "missing", None, None)
             .await
             .unwrap();
-        assert!(experiments.is_empty());
+        assert!(history.items.is_empty());
 
+        let res = store
+            .get_metric_history(run_id, "m", None, Some("bad"))
+            .await;
+        assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        let res = store.get_metric_history("missing", "m", None, None).await;
+        assert!(res.is_err());
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_search_experiments_order_by() {
+    async fn test_get_metric_history_bulk_interval() {
         dotenv::from_filename(".env_dev").ok();
-        initialize_database().await.unwrap();
         let store = SqliteStore::from_env().await.unwrap();
-        let name1 = "a";
-        let name2 = "b";
-        store.create_experiment(&name1, None, None).await.unwrap();
-        store.create_experiment(&name2, None, None).await.unwrap();
-        let experiments = store
-            .search_experiments(None, None, Some(vec!["name"]))
+        let name = Uuid::new_v4().to_string();
+        let experiment = store.create_experiment(&name, None, None).await.unwrap();
+        let mut run_ids = vec![];
+        for num_steps in [3, 101] {
+            let run = store
+                .create_run(&experiment.experiment_id, None, None, None, None)
+                .await
+                .unwrap();
+            let metrics = (0..num_steps)
+                .flat_map(|step| {
+                    [0, 1].into_iter().map(move |timestamp| Metric {
+                        key: "m".to_string(),
+                        value: step as f64,
+                        timestamp,
+                        step,
+                    })
+                })
+                .collect::<Vec<_>>();
+            for chunk in metrics.chunks(1000) {
+                store
+                    .log_batch(&run.info.run_id, chunk.iter().collect(), vec![], vec![])
+                    .await
+                    .unwrap();
+            }
+            run_ids.push(run.info.run_id);
+        }
+        let (short_run, long_run) = (run_ids[0].as_str(), run_ids[1].as_str());
+
+        let steps_of = |metrics: &[MetricWithRunId], run_id: &str| {
+            metrics
+                .iter()
+                .filter(|m| m.run_id == run_id)
+                .map(|m| m.step)
+                .collect::<Vec<_>>()
+        };
+        let metrics = store
+            .get_metric_history_bulk_interval(vec![long_run, short_run], "m", None, None, Some(5))
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 2);
-        assert_eq!(experiments[0].name, "a");
-        assert_eq!(experiments[1].name, "b");
+        assert_eq!(metrics.first().unwrap().run_id, long_run);
+        assert_eq!(metrics.last().unwrap().run_id, short_run);
+        assert_eq!(
+            steps_of(&metrics, long_run),
+            vec![0, 0, 25, 25, 50, 50, 75, 75, 100, 100]
+        );
+        assert_eq!(steps_of(&metrics, short_run), vec![0, 0, 1, 1, 2, 2]);
+        assert_eq!(
+            metrics
+                .iter()
+                .take(2)
+                .map(|m| m.timestamp)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
 
-        let experiments = store
-            .search_experiments(None, None, Some(vec!["name DESC"]))
+        let metrics = store
+            .get_metric_history_bulk_interval(vec![long_run], "m", Some(10), Some(20), Some(3))
             .await
             .unwrap();
-        assert_eq!(experiments.len(), 2);
-        assert_eq!(experiments[0].name, "b");
-        assert_eq!(experiments[1].name, "a");
+        assert_eq!(steps_of(&metrics, long_run), vec![10, 10, 15, 15, 20, 20]);
+
+        let metrics = store
+            .get_metric_history_bulk_interval(vec![long_run, short_run], "m", None, None, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(steps_of(&metrics, long_run), vec![0, 0]);
+        assert_eq!(steps_of(&metrics, short_run), vec![0, 0]);
+
+        let metrics = store
+            .get_metric_history_bulk_interval(vec![long_run, short_run], "m", None, None, Some(2))
+            .await
+            .unwrap();
+        assert_eq!(steps_of(&metrics, long_run), vec![0, 0, 100, 100]);
+        assert_eq!(steps_of(&metrics, short_run), vec![0, 0, 2, 2]);
+
+        let metrics = store
+            .get_metric_history_bulk_interval(vec![long_run], "m", None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(metrics.len(), 202);
 
+        for (run_ids, start_step, end_step, max_results) in [
+            (vec![], None, None, None),
+            (vec![long_run], Some(2), Some(1), None),
+            (vec![long_run], None, None, Some(0)),
+            (vec![long_run], None, None, Some(1001)),
+        ] {
+            let res = store
+                .get_metric_history_bulk_interval(run_ids, "m", start_step, end_step, max_results)
+                .await;
+            assert!(matches!(res, Err(MlflowError::InvalidParameter(_))));
+        }
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_search_experiments_filter_string_containing_invalid_entities() {
+    async fn test_resource_errors() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
-        let filter_string = format!("param.key = 'value'");
+        let res = store.get_experiment(&i32::MAX.to_string()).await;
+        assert!(matches!(res, Err(MlflowError::ResourceDoesNotExist(_))));
+        let res = store.get_run("missing").await;
+        assert!(matches!(res, Err(MlflowError::ResourceDoesNotExist(_))));
         let res = store
-            .search_experiments(None, Some(filter_string.as_str()), None)
+            .log_param(
+                "missing",
+                &Param {
+                    key: "key".to_string(),
+                    value: "value".to_string(),
+                },
+            )
             .await;
-        assert!(res.is_err());
+        assert!(matches!(res, Err(MlflowError::ResourceDoesNotExist(_))));
+
+        // A unique violation on the experiment name
+        let name = Uuid::new_v4().to_string();
+        sqlx::query("INSERT INTO experiments (name, artifact_location, lifecycle_stage) VALUES ($1, '', 'active')")
+            .bind(&name)
+            .execute(&store.connection)
+            .await
+            .unwrap();
+        let res: Result<_, MlflowError> = sqlx::query("INSERT INTO experiments (name, artifact_location, lifecycle_stage) VALUES ($1, '', 'active')")
+            .bind(&name)
+            .execute(&store.connection)
+            .await
+            .map_err(MlflowError::from);
+        assert!(matches!(res, Err(MlflowError::ResourceAlreadyExists(_))));
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_delete_experiment() {
+    async fn test_set_and_delete_experiment_tag() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
         let name = Uuid::new_v4().to_string();
         let experiment = store.create_experiment(&name, None, None).await.unwrap();
-        let deleted_experiment = store
-            .delete_experiment(&experiment.experiment_id)
+        let experiment_id = experiment.experiment_id.as_str();
+        for value in ["v1", "v2"] {
+            store
+                .set_experiment_tag(
+                    experiment_id,
+                    &ExperimentTag {
+                        key: "k".to_string(),
+                        value: value.to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        // Setting an existing key overwrites its value
+        let experiment = store.get_experiment(experiment_id).await.unwrap();
+        assert_eq!(
+            experiment.tags,
+            vec![ExperimentTag {
+                key: "k".to_string(),
+                value: "v2".to_string(),
+            }]
+        );
+
+        store
+            .delete_experiment_tag(experiment_id, "k")
             .await
             .unwrap();
-        assert_eq!(deleted_experiment.lifecycle_stage, "deleted");
+        let experiment = store.get_experiment(experiment_id).await.unwrap();
+        assert!(experiment.tags.is_empty());
+        assert!(matches!(
+            store.delete_experiment_tag(experiment_id, "k").await,
+            Err(MlflowError::ResourceDoesNotExist(_))
+        ));
+
+        assert!(matches!(
+            store
+                .delete_experiment_tag(&i32::MAX.to_string(), "k")
+                .await,
+            Err(MlflowError::ResourceDoesNotExist(_))
+        ));
+        store.delete_experiment(experiment_id).await.unwrap();
+        let result = store
+            .set_experiment_tag(
+                experiment_id,
+                &ExperimentTag {
+                    key: "k".to_string(),
+                    value: "v".to_string(),
+                },
+            )
+            .await;
+        match result {
+            Err(MlflowError::InvalidParameter(message)) => assert_eq!(
+                message,
+                format!(
+                    "The experiment {} must be in the 'active' state. Current state is deleted.",
+                    experiment_id
+                )
+            ),
+            _ => panic!("expected an invalid parameter error"),
+        }
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_restore_experiment() {
+    async fn test_get_experiment_by_name() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
         let name = Uuid::new_v4().to_string();
         let experiment = store.create_experiment(&name, None, None).await.unwrap();
-        let deleted_experiment = store
+        // Deleted experiments are found by name too
+        store
             .delete_experiment(&experiment.experiment_id)
             .await
             .unwrap();
-        let restored_experiment = store
-            .restore_experiment(&deleted_experiment.experiment_id)
-            .await
-            .unwrap();
-        assert_eq!(restored_experiment.lifecycle_stage, "active");
+        let found = store.get_experiment_by_name(&name).await.unwrap();
+        assert_eq!(found.experiment_id, experiment.experiment_id);
+        assert_eq!(found.lifecycle_stage, "deleted");
+        assert!(matches!(
+            store
+                .get_experiment_by_name(&Uuid::new_v4().to_string())
+                .await,
+            Err(MlflowError::ResourceDoesNotExist(_))
+        ));
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_update_experiment() {
+    async fn test_duplicate_experiment_name() {
         dotenv::from_filename(".env_dev").ok();
         let store = SqliteStore::from_env().await.unwrap();
         let name = Uuid::new_v4().to_string();
-        let experiment = store.create_experiment(&name, None, None).await.unwrap();
-        let new_name = Uuid::new_v4().to_string();
-        let updated_experiment = store
-            .update_experiment(&experiment.experiment_id, &new_name)
+        store.create_experiment(&name, None, None).await.unwrap();
+        let expected = format!("Experiment(name={}) already exists", name);
+        for artifact_location in [None, Some("/tmp/artifacts")] {
+            match store
+                .create_experiment(&name, artifact_location, None)
+                .await
+            {
+                Err(MlflowError::ResourceAlreadyExists(message)) => assert_eq!(message, expected),
+                _ => panic!("expected a resource already exists error"),
+            }
+        }
+        let other = store
+            .create_experiment(&Uuid::new_v4().to_string(), None, None)
             .await
             .unwrap();
-        assert_eq!(updated_experiment.name, new_name);
+        match store.update_experiment(&other.experiment_id, &name).await {
+            Err(MlflowError::ResourceAlreadyExists(message)) => assert_eq!(message, expected),
+            _ => panic!("expected a resource already exists error"),
+        }
         store.teardown().await;
     }
 
     #[tokio::test]
-    async fn test_search_runs() {
-        dotenv::from_filename(".env_dev").ok();
-        let store = SqliteStore::from_env().await.unwrap();
-        let runs = store.search_runs(vec!["0"]).await.unwrap();
-        assert!(runs.len() > 0);
+    async fn test_initialize_schema() {
+        let path = std::env::temp_dir().join(format!("{}.sqlite", Uuid::new_v4()));
+        let db_uri = format!("sqlite://{}", path.display());
+        let store = SqliteStore::new(&db_uri, "./artifacts", &PoolConfig::default())
+            .await
+            .unwrap();
+        let version: String = sqlx::query_scalar("SELECT version_num FROM alembic_version")
+            .fetch_one(&store.connection)
+            .await
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        // The model registry tables are part of the schema revision too
+        for table in [
+            "registered_models",
+            "model_versions",
+            "registered_model_tags",
+            "model_version_tags",
+        ] {
+            sqlx::query(&format!("SELECT * FROM {}", table))
+                .fetch_all(&store.connection)
+                .await
+                .unwrap();
+        }
+        let experiment = store.get_experiment("0").await.unwrap();
+        assert_eq!(experiment.name, "Default");
+        assert_eq!(experiment.artifact_location, "./artifacts/0");
+        let run = store
+            .create_run(&experiment.experiment_id, None, None, None, None)
+            .await
+            .unwrap();
+        store
+            .log_batch(
+                &run.info.run_id,
+                vec![&Metric {
+                    key: "m".to_string(),
+                    value: f64::NAN,
+                    timestamp: 0,
+                    step: 0,
+                }],
+                vec![&Param {
+                    key: "p".to_string(),
+                    value: "v".to_string(),
+                }],
+                vec![],
+            )
+            .await
+            .unwrap();
+        store.teardown().await;
+
+        // Reopening a database created by the migrations is a no-op
+        let store = SqliteStore::new(&db_uri, "./other", &PoolConfig::default())
+            .await
+            .unwrap();
+        let experiments = store.list_experiments(None, None).await.unwrap().items;
+        assert_eq!(experiments.len(), 1);
+        assert_eq!(experiments[0].artifact_location, "./artifacts/0");
         store.teardown().await;
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_initialize_schema_unknown_version() {
+        for statements in [
+            vec![
+                "CREATE TABLE alembic_version (version_num VARCHAR(32) NOT NULL)",
+                "INSERT INTO alembic_version VALUES ('97727af70f4d')",
+            ],
+            vec!["CREATE TABLE experiments (experiment_id INTEGER NOT NULL)"],
+        ] {
+            let path = std::env::temp_dir().join(format!("{}.sqlite", Uuid::new_v4()));
+            let db_uri = format!("sqlite://{}", path.display());
+            let connection = get_connection_pool(&db_uri, &PoolConfig::default())
+                .await
+                .unwrap();
+            for statement in statements {
+                sqlx::query(statement).execute(&connection).await.unwrap();
+            }
+            connection.close().await;
+            let res = SqliteStore::new(&db_uri, "./artifacts", &PoolConfig::default()).await;
+            assert!(matches!(res, Err(MlflowError::InvalidState(_))));
+            std::fs::remove_file(path).unwrap();
+        }
     }
 }
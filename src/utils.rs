@@ -1,5 +1,17 @@
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 pub fn random_string() -> String {
     Uuid::new_v4().to_string()
 }
+
+pub fn random_run_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+pub fn current_time_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}